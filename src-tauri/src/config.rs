@@ -1,7 +1,8 @@
+use crate::session_files::store::write_atomic;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::command;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,3 +52,91 @@ pub async fn read_codex_config() -> Result<Vec<Project>, String> {
 
     Ok(projects)
 }
+
+/// Moves the `[projects."<old>"]` table of `config.toml` to `new_path`,
+/// keeping the rest of the file (comments, ordering) as it is. Returns
+/// whether an entry was moved; an existing entry for `new_path` is left alone.
+/// The file belongs to Codex, so no lock file is created next to it.
+pub fn rename_project_in_config(old_path: &str, new_path: &str) -> Result<bool, String> {
+    rename_project_in_config_at(&get_config_path()?, old_path, new_path)
+}
+
+fn rename_project_in_config_at(
+    config_path: &Path,
+    old_path: &str,
+    new_path: &str,
+) -> Result<bool, String> {
+    if !config_path.exists() {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let mut doc = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+
+    let Some(projects) = doc
+        .get_mut("projects")
+        .and_then(|item| item.as_table_like_mut())
+    else {
+        return Ok(false);
+    };
+    if projects.contains_key(new_path) {
+        return Ok(false);
+    }
+    let Some(entry) = projects.remove(old_path) else {
+        return Ok(false);
+    };
+    projects.insert(new_path, entry);
+
+    write_atomic(config_path, doc.to_string().as_bytes())?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# Codex settings
+model = "gpt-5-codex"
+
+# Checkouts I trust
+[projects."/old"]
+trust_level = "trusted" # set by codex
+
+[projects."/elsewhere"]
+trust_level = "untrusted"
+"#;
+
+    fn write_config(contents: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, contents).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn renaming_a_project_keeps_comments_and_other_entries() {
+        let (_dir, path) = write_config(CONFIG);
+
+        assert!(rename_project_in_config_at(&path, "/old", "/new").unwrap());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content,
+            CONFIG.replace(r#"[projects."/old"]"#, r#"[projects."/new"]"#)
+        );
+    }
+
+    #[test]
+    fn an_existing_target_entry_is_left_alone() {
+        let (_dir, path) = write_config(CONFIG);
+
+        assert!(!rename_project_in_config_at(&path, "/old", "/elsewhere").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+
+        assert!(!rename_project_in_config_at(&path, "/missing", "/new").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+    }
+}
@@ -4,10 +4,40 @@ mod terminal;
 
 use config::read_codex_config;
 use session_files::{
+    archive::archive_project_sessions,
     delete::{delete_cache_file, delete_session_file, delete_sessions_files},
+    events::get_session_events,
+    export::export_session_markdown,
+    export_html::export_session_html,
+    follow::{follow_session, unfollow_session},
+    identity::{
+        get_logical_project_sessions, get_logical_projects, get_project_aliases,
+        set_project_aliases,
+    },
+    import::import_sessions,
+    index::{rebuild_search_index, search_sessions_ranked},
+    overview::get_projects_overview,
+    pricing::{get_price_table, set_price_table},
+    quarantine::{
+        find_cleanup_candidates, get_cleanup_settings, list_quarantine, purge_quarantine,
+        quarantine_sessions, restore_quarantined, set_cleanup_settings,
+    },
+    redact::{get_redaction_config, redact_session_copy, set_redaction_config},
+    relocate::relocate_project,
+    retention::{
+        apply_retention_policy, get_retention_policy, preview_retention_policy,
+        set_retention_policy,
+    },
     save::get_project_sessions,
-    scan::scan_projects,
-    update::update_cache_title,
+    scan::{cancel_session_scan, scan_projects, start_session_scan},
+    search::search_sessions,
+    trash::{empty_trash, list_trash, restore_sessions, undo_delete},
+    update::{
+        add_session_tag, remove_session_tag, set_session_notes, toggle_session_star,
+        update_session_title,
+    },
+    usage::{get_session_usage, get_token_usage},
+    watch::start_session_watcher,
 };
 use terminal::open_terminal_with_command;
 
@@ -15,15 +45,66 @@ use terminal::open_terminal_with_command;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
+        .setup(|app| {
+            // The app works without live updates, so a failed watcher is not fatal.
+            if let Err(e) = start_session_watcher(app.handle().clone()) {
+                eprintln!("{}", e);
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             scan_projects,
             get_project_sessions,
             delete_session_file,
-            update_cache_title,
+            update_session_title,
+            add_session_tag,
+            remove_session_tag,
+            toggle_session_star,
+            set_session_notes,
             read_codex_config,
             open_terminal_with_command,
             delete_cache_file,
             delete_sessions_files,
+            get_session_events,
+            search_sessions,
+            search_sessions_ranked,
+            rebuild_search_index,
+            get_token_usage,
+            get_session_usage,
+            get_price_table,
+            set_price_table,
+            get_projects_overview,
+            find_cleanup_candidates,
+            quarantine_sessions,
+            list_quarantine,
+            restore_quarantined,
+            purge_quarantine,
+            get_cleanup_settings,
+            set_cleanup_settings,
+            list_trash,
+            restore_sessions,
+            undo_delete,
+            empty_trash,
+            get_retention_policy,
+            set_retention_policy,
+            preview_retention_policy,
+            apply_retention_policy,
+            export_session_markdown,
+            export_session_html,
+            get_redaction_config,
+            set_redaction_config,
+            redact_session_copy,
+            import_sessions,
+            archive_project_sessions,
+            relocate_project,
+            get_project_aliases,
+            set_project_aliases,
+            get_logical_projects,
+            get_logical_project_sessions,
+            start_session_scan,
+            cancel_session_scan,
+            follow_session,
+            unfollow_session,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,524 @@
+use super::file::get_sessions_path;
+use super::metadata::{with_metadata, SessionEntry};
+use super::scan::scan_project_sessions;
+use super::trash::move_to_trash;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+const MANIFEST_ENTRY_NAME: &str = "manifest.json";
+pub const ARCHIVE_MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    fn from_path(path: &Path) -> Result<Self, String> {
+        let name = path.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Ok(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(ArchiveFormat::TarGz)
+        } else {
+            Err(format!(
+                "Unsupported archive file {:?}: expected .zip, .tar.gz or .tgz",
+                path
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedSession {
+    pub conversation_id: String,
+    /// Path inside the archive, mirroring the `YYYY/MM/DD` layout of the sessions directory.
+    pub entry_name: String,
+    pub original_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub total_tokens: Option<u64>,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub version: u32,
+    pub project_path: String,
+    pub created_at: DateTime<Utc>,
+    pub sessions: Vec<ArchivedSession>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveReport {
+    pub output_path: String,
+    pub session_count: usize,
+    pub total_bytes: u64,
+    /// Originals moved to the trash after the archive was verified.
+    pub removed: Vec<String>,
+    /// Originals left in place because they changed after they were archived
+    /// or could not be moved to the trash.
+    pub kept: Vec<String>,
+}
+
+/// Hashes and counts the bytes read through it, so a file is checksummed
+/// while it is copied instead of being read twice.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hasher: Sha256::new(),
+            len: 0,
+        }
+    }
+
+    fn sha256_hex(self) -> String {
+        self.hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+/// Size and checksum of everything `reader` yields.
+fn checksum(reader: impl Read) -> io::Result<(u64, String)> {
+    let mut reader = HashingReader::new(reader);
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok((reader.len, reader.sha256_hex()))
+}
+
+/// Entry name for a rollout, always with `/` separators whatever the platform.
+fn entry_name(sessions_dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(sessions_dir).unwrap_or(path);
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    format!("sessions/{}", parts.join("/"))
+}
+
+enum ArchiveWriter {
+    Zip(zip::ZipWriter<File>),
+    TarGz(tar::Builder<flate2::write::GzEncoder<File>>),
+}
+
+impl ArchiveWriter {
+    fn create(output_path: &Path, format: ArchiveFormat) -> Result<Self, String> {
+        let file = File::create(output_path)
+            .map_err(|e| format!("Failed to create archive {:?}: {}", output_path, e))?;
+        Ok(match format {
+            ArchiveFormat::Zip => ArchiveWriter::Zip(zip::ZipWriter::new(file)),
+            ArchiveFormat::TarGz => ArchiveWriter::TarGz(tar::Builder::new(
+                flate2::write::GzEncoder::new(file, flate2::Compression::default()),
+            )),
+        })
+    }
+
+    /// Streams `size` bytes from `reader` into the entry `name`. Returns the
+    /// checksum of what was written.
+    fn append(&mut self, name: &str, size: u64, reader: impl Read) -> Result<String, String> {
+        let mut reader = HashingReader::new(reader.take(size));
+        let written = match self {
+            ArchiveWriter::Zip(zip) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(size >= u32::MAX as u64);
+                zip.start_file(name, options)
+                    .map_err(io::Error::other)
+                    .and_then(|_| io::copy(&mut reader, zip).map(|_| ()))
+            }
+            ArchiveWriter::TarGz(tar) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(size);
+                header.set_mode(0o644);
+                header.set_mtime(Utc::now().timestamp().max(0) as u64);
+                tar.append_data(&mut header, name, &mut reader)
+            }
+        };
+        written.map_err(|e| format!("Failed to write {} to archive: {}", name, e))?;
+        // The header already promised `size` bytes; a file that shrank in the
+        // meantime would leave a corrupt entry.
+        if reader.len != size {
+            return Err(format!("{} changed while it was being archived", name));
+        }
+        Ok(reader.sha256_hex())
+    }
+
+    fn finish(self) -> Result<(), String> {
+        let file = match self {
+            ArchiveWriter::Zip(zip) => zip.finish().map_err(io::Error::other),
+            ArchiveWriter::TarGz(tar) => tar.into_inner().and_then(|encoder| encoder.finish()),
+        }
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to write archive: {}", e))
+    }
+}
+
+/// Checksums of every entry in a written archive, keyed by entry name.
+fn read_back_checksums(
+    output_path: &Path,
+    format: ArchiveFormat,
+) -> Result<HashMap<String, String>, String> {
+    let file = File::open(output_path)
+        .map_err(|e| format!("Failed to reopen archive {:?}: {}", output_path, e))?;
+    let mut checksums = HashMap::new();
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipArchive::new(file)
+                .map_err(|e| format!("Failed to read back archive: {}", e))?;
+            for i in 0..zip.len() {
+                let entry = zip
+                    .by_index(i)
+                    .map_err(|e| format!("Failed to read back archive: {}", e))?;
+                let name = entry.name().to_string();
+                let (_, sha256) =
+                    checksum(entry).map_err(|e| format!("Failed to read back {}: {}", name, e))?;
+                checksums.insert(name, sha256);
+            }
+        }
+        ArchiveFormat::TarGz => {
+            let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+            let entries = tar
+                .entries()
+                .map_err(|e| format!("Failed to read back archive: {}", e))?;
+            for entry in entries {
+                let entry = entry.map_err(|e| format!("Failed to read back archive: {}", e))?;
+                let name = entry
+                    .path()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let (_, sha256) =
+                    checksum(entry).map_err(|e| format!("Failed to read back {}: {}", name, e))?;
+                checksums.insert(name, sha256);
+            }
+        }
+    }
+    Ok(checksums)
+}
+
+/// Copies every session into `writer`, followed by the manifest describing them.
+fn write_archive(
+    writer: &mut ArchiveWriter,
+    project_path: &str,
+    sessions: Vec<SessionEntry>,
+    sessions_dir: &Path,
+) -> Result<ArchiveManifest, String> {
+    let mut archived = Vec::new();
+    for entry in sessions {
+        let path = Path::new(&entry.session.path);
+        let file =
+            File::open(path).map_err(|e| format!("Failed to read session {:?}: {}", path, e))?;
+        let size_bytes = file
+            .metadata()
+            .map_err(|e| format!("Failed to read session {:?}: {}", path, e))?
+            .len();
+        let entry_name = entry_name(sessions_dir, path);
+        let sha256 = writer.append(&entry_name, size_bytes, file)?;
+        let summary = &entry.session.summary;
+        archived.push(ArchivedSession {
+            conversation_id: entry.session.conversation_id.clone(),
+            entry_name,
+            original_path: entry.session.path.clone(),
+            title: entry.metadata.title.clone(),
+            tags: entry.metadata.tags.clone(),
+            started_at: summary.started_at,
+            last_event_at: summary.last_event_at,
+            total_tokens: summary.total_tokens,
+            size_bytes,
+            sha256,
+        });
+    }
+
+    // Written last, since the checksums are only known once every file is copied.
+    let manifest = ArchiveManifest {
+        version: ARCHIVE_MANIFEST_VERSION,
+        project_path: project_path.to_string(),
+        created_at: Utc::now(),
+        sessions: archived,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize archive manifest: {}", e))?;
+    writer.append(
+        MANIFEST_ENTRY_NAME,
+        manifest_json.len() as u64,
+        manifest_json.as_slice(),
+    )?;
+    Ok(manifest)
+}
+
+/// Splits the archived originals into those still identical to their
+/// archived copy and those that changed (or vanished) since.
+fn unchanged_originals(manifest: &ArchiveManifest) -> (Vec<String>, Vec<String>) {
+    let mut unchanged = Vec::new();
+    let mut changed = Vec::new();
+    for session in &manifest.sessions {
+        let same = File::open(&session.original_path)
+            .and_then(checksum)
+            .is_ok_and(|(_, sha256)| sha256 == session.sha256);
+        if same {
+            unchanged.push(session.original_path.clone());
+        } else {
+            changed.push(session.original_path.clone());
+        }
+    }
+    (unchanged, changed)
+}
+
+/// Writes every rollout of a project, plus a `manifest.json`, to a zip or
+/// `.tar.gz` archive. With `remove_originals` the rollouts are moved to the
+/// trash, but only after the archive has been read back and every checksum
+/// matches.
+#[tauri::command]
+pub async fn archive_project_sessions(
+    project_path: String,
+    output_path: String,
+    format: Option<ArchiveFormat>,
+    remove_originals: Option<bool>,
+) -> Result<ArchiveReport, String> {
+    let output_path = PathBuf::from(output_path);
+    let format = match format {
+        Some(format) => format,
+        None => ArchiveFormat::from_path(&output_path)?,
+    };
+    let sessions_dir = get_sessions_path()?;
+    let sessions = with_metadata(scan_project_sessions(&project_path)?)?;
+    if sessions.is_empty() {
+        return Err(format!("No sessions found for {}", project_path));
+    }
+
+    // Build next to the destination and rename, so a failed run never leaves
+    // a truncated archive under the requested name.
+    let mut partial_name = output_path.as_os_str().to_os_string();
+    partial_name.push(".partial");
+    let partial_path = PathBuf::from(partial_name);
+    let written = ArchiveWriter::create(&partial_path, format).and_then(|mut writer| {
+        let manifest = write_archive(&mut writer, &project_path, sessions, &sessions_dir)?;
+        writer.finish()?;
+        std::fs::rename(&partial_path, &output_path)
+            .map_err(|e| format!("Failed to move archive into place: {}", e))?;
+        Ok(manifest)
+    });
+    let manifest = match written {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial_path);
+            return Err(e);
+        }
+    };
+
+    let mut removed = Vec::new();
+    let mut kept = Vec::new();
+    if remove_originals.unwrap_or(false) {
+        let checksums = read_back_checksums(&output_path, format)?;
+        let mismatched: Vec<&str> = manifest
+            .sessions
+            .iter()
+            .filter(|s| checksums.get(&s.entry_name) != Some(&s.sha256))
+            .map(|s| s.entry_name.as_str())
+            .collect();
+        if !mismatched.is_empty() {
+            return Err(format!(
+                "Archive verification failed, originals kept: {}",
+                mismatched.join(", ")
+            ));
+        }
+
+        // Only files that have not changed since they were archived go.
+        let (unchanged, changed) = unchanged_originals(&manifest);
+        kept = changed;
+        for outcome in move_to_trash(&project_path, &unchanged)? {
+            match outcome.error {
+                None => removed.push(outcome.path),
+                Some(e) => {
+                    eprintln!("Failed to remove {}: {}", outcome.path, e);
+                    kept.push(outcome.path);
+                }
+            }
+        }
+    }
+
+    Ok(ArchiveReport {
+        output_path: output_path.to_string_lossy().to_string(),
+        session_count: manifest.sessions.len(),
+        total_bytes: manifest.sessions.iter().map(|s| s.size_bytes).sum(),
+        removed,
+        kept,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_files::cache::CachedSession;
+    use crate::session_files::metadata::SessionMetadata;
+    use crate::session_files::summary::SessionSummary;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    fn session(sessions_dir: &Path, id: &str) -> SessionEntry {
+        let path = sessions_dir
+            .join("2025/09/20")
+            .join(format!("rollout-2025-09-20T10-00-00-{}.jsonl", id));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, ROLLOUT.replace("0199-abc", id)).unwrap();
+        SessionEntry {
+            session: CachedSession {
+                path: path.to_string_lossy().to_string(),
+                conversation_id: id.to_string(),
+                preview: String::new(),
+                summary: SessionSummary {
+                    total_tokens: Some(42),
+                    ..Default::default()
+                },
+            },
+            metadata: SessionMetadata {
+                title: Some(format!("Title {}", id)),
+                tags: vec!["keep".to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    fn read_manifest(output_path: &Path, format: ArchiveFormat) -> ArchiveManifest {
+        let file = File::open(output_path).unwrap();
+        let mut json = String::new();
+        match format {
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipArchive::new(file).unwrap();
+                zip.by_name(MANIFEST_ENTRY_NAME)
+                    .unwrap()
+                    .read_to_string(&mut json)
+                    .unwrap();
+            }
+            ArchiveFormat::TarGz => {
+                let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+                let mut entry = tar
+                    .entries()
+                    .unwrap()
+                    .map(|entry| entry.unwrap())
+                    .find(|entry| entry.path().unwrap().to_string_lossy() == MANIFEST_ENTRY_NAME)
+                    .unwrap();
+                entry.read_to_string(&mut json).unwrap();
+            }
+        }
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn archive(dir: &Path, name: &str, sessions: Vec<SessionEntry>) -> (PathBuf, ArchiveManifest) {
+        let output_path = dir.join(name);
+        let format = ArchiveFormat::from_path(&output_path).unwrap();
+        let mut writer = ArchiveWriter::create(&output_path, format).unwrap();
+        let manifest =
+            write_archive(&mut writer, "/home/me/app", sessions, &dir.join("sessions")).unwrap();
+        writer.finish().unwrap();
+        (output_path, manifest)
+    }
+
+    #[test]
+    fn manifest_round_trips_through_both_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions_dir = dir.path().join("sessions");
+        for name in ["out.zip", "out.tar.gz"] {
+            let sessions = vec![
+                session(&sessions_dir, "0199-a"),
+                session(&sessions_dir, "0199-b"),
+            ];
+            let (output_path, written) = archive(dir.path(), name, sessions);
+            let format = ArchiveFormat::from_path(&output_path).unwrap();
+
+            let read = read_manifest(&output_path, format);
+            assert_eq!(read.version, ARCHIVE_MANIFEST_VERSION);
+            assert_eq!(read.project_path, "/home/me/app");
+            assert_eq!(read.created_at, written.created_at);
+            assert_eq!(read.sessions.len(), 2);
+            let first = &read.sessions[0];
+            assert_eq!(first.conversation_id, "0199-a");
+            assert_eq!(
+                first.entry_name,
+                "sessions/2025/09/20/rollout-2025-09-20T10-00-00-0199-a.jsonl"
+            );
+            assert_eq!(first.title.as_deref(), Some("Title 0199-a"));
+            assert_eq!(first.tags, vec!["keep"]);
+            assert_eq!(first.total_tokens, Some(42));
+            assert_eq!(
+                first.size_bytes,
+                std::fs::metadata(&first.original_path).unwrap().len()
+            );
+
+            let checksums = read_back_checksums(&output_path, format).unwrap();
+            for session in &read.sessions {
+                assert_eq!(checksums[&session.entry_name], session.sha256);
+            }
+        }
+    }
+
+    #[test]
+    fn changed_originals_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions_dir = dir.path().join("sessions");
+        let sessions = vec![
+            session(&sessions_dir, "0199-a"),
+            session(&sessions_dir, "0199-b"),
+            session(&sessions_dir, "0199-c"),
+        ];
+        let (_, manifest) = archive(dir.path(), "out.zip", sessions);
+        let paths: Vec<String> = manifest
+            .sessions
+            .iter()
+            .map(|session| session.original_path.clone())
+            .collect();
+        // Codex appended to one session and another was deleted meanwhile.
+        let mut file = File::options().append(true).open(&paths[1]).unwrap();
+        std::io::Write::write_all(&mut file, b"{}\n").unwrap();
+        std::fs::remove_file(&paths[2]).unwrap();
+
+        let (unchanged, changed) = unchanged_originals(&manifest);
+        assert_eq!(unchanged, vec![paths[0].clone()]);
+        assert_eq!(changed, vec![paths[1].clone(), paths[2].clone()]);
+    }
+
+    #[test]
+    fn unknown_extensions_are_rejected() {
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("a.TGZ")).unwrap(),
+            ArchiveFormat::TarGz
+        );
+        assert!(ArchiveFormat::from_path(Path::new("a.7z")).is_err());
+        assert!(ArchiveFormat::from_path(Path::new("a.tar")).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use super::catalog::with_catalog;
+use super::get::get_cache_dir;
+use super::metadata::{get_metadata_path, update_metadata_at};
+use super::summary::SessionSummary;
+use super::utils::extract_datetime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedSession {
+    pub path: String,
+    pub conversation_id: String,
+    #[serde(default)]
+    pub preview: String,
+    #[serde(flatten)]
+    pub summary: SessionSummary,
+}
+
+/// Newest sessions first, using the timestamp encoded in the rollout path.
+pub fn sort_sessions(sessions: &mut [CachedSession]) {
+    sessions.sort_by(|a, b| {
+        let a_dt = extract_datetime(&a.path);
+        let b_dt = extract_datetime(&b.path);
+        match (a_dt, b_dt) {
+            (Some(a_dt), Some(b_dt)) => b_dt.cmp(&a_dt),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.path.cmp(&b.path),
+        }
+    });
+}
+
+/// A session from a project cache written before the session catalog. The
+/// old `update_cache_title` stored custom titles in `preview`.
+#[derive(Debug, Clone, PartialEq)]
+struct LegacySession {
+    conversation_id: String,
+    preview: String,
+}
+
+/// Project caches, `scan_cache/<base64 project path>.json`, left over from
+/// before the session catalog. Other files in `scan_cache` are not matched.
+fn legacy_cache_files(cache_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_file())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter(|path| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|stem| {
+                    stem.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='))
+                })
+        })
+        .collect()
+}
+
+/// Reads the sessions of a legacy cache of any schema version. Entries
+/// missing the fields we rely on are dropped; a corrupt cache is treated as
+/// empty.
+fn read_legacy_cache(cache_path: &Path) -> Vec<LegacySession> {
+    let cache = read_to_string(cache_path)
+        .map_err(|e| format!("Failed to read cache: {}", e))
+        .and_then(|s| {
+            serde_json::from_str::<Value>(&s)
+                .map_err(|e| format!("Failed to parse cache JSON: {}", e))
+        });
+    let cache = match cache {
+        Ok(cache) => cache,
+        Err(e) => {
+            eprintln!("Discarding cache {:?}: {}", cache_path, e);
+            return Vec::new();
+        }
+    };
+    cache["sessions"]
+        .as_array()
+        .map(|sessions| {
+            sessions
+                .iter()
+                .filter(|item| item["path"].as_str().is_some())
+                .filter_map(|item| {
+                    Some(LegacySession {
+                        conversation_id: item["conversationId"].as_str()?.to_string(),
+                        preview: item["preview"].as_str().unwrap_or_default().to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Moves custom titles out of the legacy project caches in `cache_dir` into
+/// the metadata store at `metadata_path`, then removes the caches. A cached
+/// preview that differs from the one read from the rollout (`previews`, by
+/// conversation id) was set by a rename. Titles already in the metadata store
+/// win. Returns how many titles were migrated.
+pub fn migrate_legacy_caches_in(
+    cache_dir: &Path,
+    metadata_path: &Path,
+    previews: &HashMap<String, String>,
+) -> Result<usize, String> {
+    let cache_files = legacy_cache_files(cache_dir);
+    if cache_files.is_empty() {
+        return Ok(0);
+    }
+
+    let titles: Vec<LegacySession> = cache_files
+        .iter()
+        .flat_map(|path| read_legacy_cache(path))
+        .filter(|session| !session.preview.trim().is_empty())
+        .filter(|session| {
+            previews
+                .get(&session.conversation_id)
+                .is_some_and(|preview| *preview != session.preview)
+        })
+        .collect();
+
+    let migrated = update_metadata_at(metadata_path, |store| {
+        let mut migrated = 0;
+        for session in titles {
+            let metadata = store.sessions.entry(session.conversation_id).or_default();
+            if metadata.title.is_none() {
+                metadata.title = Some(session.preview.trim().to_string());
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    })?;
+
+    // Only once the titles are safe, so a failed write retries next launch.
+    for path in cache_files {
+        let lock_path = path.with_extension("json.lock");
+        for path in [path, lock_path] {
+            if let Err(e) = std::fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!("Failed to remove legacy cache {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+    Ok(migrated)
+}
+
+/// One-time migration of the legacy project caches; a no-op once they are gone.
+pub fn migrate_legacy_caches() -> Result<usize, String> {
+    let cache_dir = get_cache_dir()?;
+    if legacy_cache_files(&cache_dir).is_empty() {
+        return Ok(0);
+    }
+    let previews = with_catalog(|catalog| {
+        catalog
+            .entries
+            .values()
+            .filter_map(|entry| entry.session.as_ref())
+            .map(|session| (session.conversation_id.clone(), session.preview.clone()))
+            .collect()
+    })?;
+    migrate_legacy_caches_in(&cache_dir, &get_metadata_path()?, &previews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_files::metadata::load_metadata_at;
+
+    fn previews() -> HashMap<String, String> {
+        [("a", "fix the build"), ("b", "add a test")]
+            .into_iter()
+            .map(|(id, preview)| (id.to_string(), preview.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn unversioned_cache_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata_path = dir.path().join("session_metadata.json");
+        std::fs::write(
+            dir.path().join("L2hvbWUvbWUvYXBw.json"),
+            r#"{"last_scanned":"2025-01-01T00:00:00Z","sessions":[
+                {"path":"/a.jsonl","conversationId":"a","preview":"Build fixes"},
+                {"path":"/b.jsonl","conversationId":"b","preview":"add a test"},
+                {"conversationId":"c","preview":"broken"}]}"#,
+        )
+        .unwrap();
+
+        let migrated = migrate_legacy_caches_in(dir.path(), &metadata_path, &previews()).unwrap();
+        assert_eq!(migrated, 1);
+        let store = load_metadata_at(&metadata_path).unwrap();
+        assert_eq!(store.sessions["a"].title.as_deref(), Some("Build fixes"));
+        assert!(!store.sessions.contains_key("b"));
+        assert!(!store.sessions.contains_key("c"));
+    }
+
+    #[test]
+    fn renamed_titles_move_to_metadata_and_caches_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata_path = dir.path().join("session_metadata.json");
+        let cache_path = dir.path().join("L2hvbWUvbWUvYXBw.json");
+        std::fs::write(
+            &cache_path,
+            r#"{"schema_version":2,"last_scanned":"2025-01-01T00:00:00Z","sessions":[
+                {"path":"/a.jsonl","conversationId":"a","preview":"Build fixes"},
+                {"path":"/b.jsonl","conversationId":"b","preview":"Tests"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(cache_path.with_extension("json.lock"), "").unwrap();
+        std::fs::write(dir.path().join("session_catalog.json"), "{}").unwrap();
+        std::fs::create_dir(dir.path().join("search_index")).unwrap();
+        update_metadata_at(&metadata_path, |store| {
+            store.sessions.entry("b".to_string()).or_default().title = Some("Mine".to_string());
+            Ok(())
+        })
+        .unwrap();
+
+        let migrated = migrate_legacy_caches_in(dir.path(), &metadata_path, &previews()).unwrap();
+        assert_eq!(migrated, 1);
+        let store = load_metadata_at(&metadata_path).unwrap();
+        assert_eq!(store.sessions["a"].title.as_deref(), Some("Build fixes"));
+        assert_eq!(store.sessions["b"].title.as_deref(), Some("Mine"));
+
+        let mut names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "search_index",
+                "session_catalog.json",
+                "session_metadata.json",
+                "session_metadata.json.lock"
+            ]
+        );
+        assert_eq!(
+            migrate_legacy_caches_in(dir.path(), &metadata_path, &previews()).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn corrupt_cache_is_treated_as_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata_path = dir.path().join("session_metadata.json");
+        let cache_path = dir.path().join("L2hvbWUvbWUvYXBw.json");
+        std::fs::write(&cache_path, "{\"sessions\": [").unwrap();
+
+        let migrated = migrate_legacy_caches_in(dir.path(), &metadata_path, &previews()).unwrap();
+        assert_eq!(migrated, 0);
+        assert!(!cache_path.exists());
+    }
+}
@@ -0,0 +1,910 @@
+use super::cache::CachedSession;
+use super::file::get_sessions_path;
+use super::get::get_cache_dir;
+use super::scan::{cached_session, scan_jsonl_files};
+use super::store::{with_file_lock, write_atomic};
+use super::summary::summarize_session;
+use super::utils::file_id;
+use super::watch::is_watching;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, LazyLock, Mutex, MutexGuard};
+
+const CATALOG_FILE_NAME: &str = "session_catalog.json";
+/// Rollouts are read from disk, so a few threads saturate it.
+const MAX_SCAN_WORKERS: usize = 8;
+/// Bump when the entry layout changes; older catalogs are rebuilt from scratch.
+pub const CATALOG_SCHEMA_VERSION: u32 = 3;
+
+/// What the app needs to know about one rollout without opening it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub cwd: Option<String>,
+    /// Remote URL the session recorded for its checkout.
+    pub git_remote: Option<String>,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub size_bytes: u64,
+    pub inode: Option<u64>,
+    pub line_count: usize,
+    /// Whether the first line is a `session_meta` record with an id.
+    pub has_session_meta: bool,
+    /// `None` for files that are not readable sessions.
+    pub session: Option<CachedSession>,
+}
+
+impl CatalogEntry {
+    pub fn read(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let rollout = summarize_session(path).ok();
+        let first_line = rollout.as_ref().and_then(|r| r.first_line.as_ref());
+        let has_session_meta = first_line.is_some_and(|value| {
+            value["type"] == "session_meta" && value["payload"]["id"].is_string()
+        });
+        Some(CatalogEntry {
+            cwd: first_line
+                .and_then(|value| value["payload"]["cwd"].as_str())
+                .map(str::to_string),
+            git_remote: first_line
+                .and_then(|value| value["payload"]["git"]["repository_url"].as_str())
+                .map(str::to_string),
+            modified_at: metadata.modified().ok().map(Into::into),
+            size_bytes: metadata.len(),
+            inode: file_id(&metadata),
+            line_count: rollout.as_ref().map_or(0, |r| r.line_count),
+            has_session_meta,
+            session: rollout
+                .and_then(|r| r.session)
+                .map(|(info, summary)| cached_session(path, info, summary)),
+        })
+    }
+
+    /// Whether the file on disk is still the one this entry was read from.
+    /// A different inode means it was replaced, even with the same size and mtime.
+    fn is_current(&self, metadata: &std::fs::Metadata) -> bool {
+        self.fingerprint() == fingerprint(metadata)
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        (self.inode, self.size_bytes, self.modified_at)
+    }
+}
+
+/// (inode, size, mtime) of a rollout.
+type Fingerprint = (Option<u64>, u64, Option<DateTime<Utc>>);
+
+fn fingerprint(metadata: &std::fs::Metadata) -> Fingerprint {
+    (
+        file_id(metadata),
+        metadata.len(),
+        metadata.modified().ok().map(Into::into),
+    )
+}
+
+/// The catalog shared by every command, loaded on first use.
+static CATALOG: LazyLock<Mutex<Option<LoadedCatalog>>> = LazyLock::new(|| Mutex::new(None));
+
+struct LoadedCatalog {
+    catalog: SessionCatalog,
+    /// Changes not written to disk yet.
+    dirty: bool,
+    /// Whether the last walk saw every file, i.e. was not cancelled.
+    complete: bool,
+}
+
+/// Every rollout under the sessions directory, keyed by path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCatalog {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl Default for SessionCatalog {
+    fn default() -> Self {
+        SessionCatalog {
+            schema_version: CATALOG_SCHEMA_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl SessionCatalog {
+    /// Sessions of one project, in no particular order.
+    pub fn project_sessions(&self, project_path: &str) -> Vec<CachedSession> {
+        self.entries
+            .values()
+            .filter(|entry| entry.cwd.as_deref() == Some(project_path))
+            .filter_map(|entry| entry.session.clone())
+            .collect()
+    }
+
+    /// Entries of readable sessions, grouped by project.
+    pub fn entries_by_project(&self) -> HashMap<&str, Vec<&CatalogEntry>> {
+        let mut projects: HashMap<&str, Vec<&CatalogEntry>> = HashMap::new();
+        for entry in self
+            .entries
+            .values()
+            .filter(|entry| entry.session.is_some())
+        {
+            if let Some(cwd) = entry.cwd.as_deref() {
+                projects.entry(cwd).or_default().push(entry);
+            }
+        }
+        projects
+    }
+
+    /// Catalogued rollouts under `dir`.
+    pub fn paths_under(&self, dir: &Path) -> Vec<PathBuf> {
+        self.entries
+            .keys()
+            .map(PathBuf::from)
+            .filter(|path| path.starts_with(dir))
+            .collect()
+    }
+}
+
+pub fn get_catalog_path() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join(CATALOG_FILE_NAME))
+}
+
+/// Reads the catalog, starting over if it is missing, unreadable or from
+/// another schema version. It only holds derived data, so nothing is lost.
+fn read_catalog(catalog_path: &Path) -> SessionCatalog {
+    if !catalog_path.exists() {
+        return SessionCatalog::default();
+    }
+    let catalog = read_to_string(catalog_path)
+        .map_err(|e| format!("Failed to read session catalog: {}", e))
+        .and_then(|catalog_str| {
+            serde_json::from_str::<SessionCatalog>(&catalog_str)
+                .map_err(|e| format!("Failed to parse session catalog: {}", e))
+        });
+    match catalog {
+        Ok(catalog) if catalog.schema_version == CATALOG_SCHEMA_VERSION => catalog,
+        Ok(catalog) => {
+            eprintln!(
+                "Rebuilding session catalog from version {}",
+                catalog.schema_version
+            );
+            SessionCatalog::default()
+        }
+        Err(e) => {
+            eprintln!("Rebuilding session catalog: {}", e);
+            SessionCatalog::default()
+        }
+    }
+}
+
+/// Reported for every rollout `refresh_catalog_with` finds, whether it was
+/// read again or still current.
+pub struct CatalogProgress<'a> {
+    pub entry: &'a CatalogEntry,
+    pub done: usize,
+    pub total: usize,
+}
+
+fn worker_count() -> usize {
+    std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_SCAN_WORKERS)
+}
+
+/// Reads `paths` on a bounded pool of worker threads and hands each result
+/// to `on_read` on the calling thread as soon as it is ready. Workers stop
+/// picking up new files once `cancel` is set.
+fn read_entries_parallel(
+    paths: Vec<String>,
+    cancel: &AtomicBool,
+    mut on_read: impl FnMut(String, Option<CatalogEntry>),
+) {
+    let queue = Mutex::new(paths.into_iter());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..worker_count() {
+            let sender = sender.clone();
+            let queue = &queue;
+            scope.spawn(move || {
+                while !cancel.load(Ordering::Relaxed) {
+                    // A panicking worker never holds the lock while reading.
+                    let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                    let Some(path) = next else {
+                        break;
+                    };
+                    let entry = CatalogEntry::read(Path::new(&path));
+                    if sender.send((path, entry)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (path, entry) in receiver {
+            on_read(path, entry);
+        }
+    });
+}
+
+/// Reconciles `previous` with the rollouts under `sessions_dir` in one walk.
+/// Unchanged files (same size and mtime) are only stat'ed; new and changed
+/// files are read once and remembered, and vanished files are dropped.
+/// Returns the new catalog and whether it differs from `previous`.
+fn reconcile(
+    mut previous: SessionCatalog,
+    sessions_dir: &Path,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(CatalogProgress),
+) -> (SessionCatalog, bool) {
+    let files: Vec<(String, std::fs::Metadata)> = scan_jsonl_files(sessions_dir)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((entry.path().to_string_lossy().to_string(), metadata))
+        })
+        .collect();
+    let total = files.len();
+    let mut done = 0;
+    let mut catalog = SessionCatalog::default();
+    let mut to_read = Vec::new();
+    let mut new_files = Vec::new();
+
+    for (path, metadata) in files {
+        match previous.entries.remove(&path) {
+            Some(cached) if cached.is_current(&metadata) => {
+                done += 1;
+                on_progress(CatalogProgress {
+                    entry: &cached,
+                    done,
+                    total,
+                });
+                catalog.entries.insert(path, cached);
+            }
+            Some(stale) => {
+                // Kept until the file is read again, so a cancelled scan
+                // does not forget it; the stale fingerprint forces a re-read.
+                catalog.entries.insert(path.clone(), stale);
+                to_read.push(path);
+            }
+            None => new_files.push((path, metadata)),
+        }
+    }
+
+    // What is left of `previous` is gone from its path. A new path with
+    // the same inode, size and mtime is that file renamed or moved, and
+    // keeps its entry instead of being read again.
+    let mut vanished: HashMap<Fingerprint, String> = previous
+        .entries
+        .iter()
+        .filter(|(_, entry)| entry.inode.is_some())
+        .map(|(path, entry)| (entry.fingerprint(), path.clone()))
+        .collect();
+    let mut renamed = false;
+    for (path, metadata) in new_files {
+        let moved = vanished
+            .remove(&fingerprint(&metadata))
+            .and_then(|old_path| previous.entries.remove(&old_path));
+        match moved {
+            Some(mut entry) => {
+                renamed = true;
+                if let Some(session) = &mut entry.session {
+                    session.path = path.clone();
+                }
+                done += 1;
+                on_progress(CatalogProgress {
+                    entry: &entry,
+                    done,
+                    total,
+                });
+                catalog.entries.insert(path, entry);
+            }
+            None => to_read.push(path),
+        }
+    }
+
+    let changed = renamed || !to_read.is_empty() || !previous.entries.is_empty();
+    read_entries_parallel(to_read, cancel, |path, entry| {
+        done += 1;
+        match entry {
+            Some(entry) => {
+                on_progress(CatalogProgress {
+                    entry: &entry,
+                    done,
+                    total,
+                });
+                catalog.entries.insert(path, entry);
+            }
+            None => {
+                catalog.entries.remove(&path);
+            }
+        }
+    });
+    (catalog, changed)
+}
+
+fn write_catalog(catalog_path: &Path, catalog: &SessionCatalog) -> Result<(), String> {
+    let json_str = serde_json::to_string(catalog)
+        .map_err(|e| format!("Failed to serialize session catalog: {}", e))?;
+    with_file_lock(catalog_path, || {
+        write_atomic(catalog_path, json_str.as_bytes())
+    })
+}
+
+fn lock_catalog() -> Result<MutexGuard<'static, Option<LoadedCatalog>>, String> {
+    CATALOG
+        .lock()
+        .map_err(|e| format!("Session catalog lock poisoned: {}", e))
+}
+
+/// Walks `sessions_dir` and replaces the in-memory catalog, starting from
+/// the one at `catalog_path` the first time. A cancelled refresh keeps what
+/// it read but is not `complete`, so the next use walks again. Returns
+/// whether the walk was complete.
+fn refresh_locked(
+    slot: &mut Option<LoadedCatalog>,
+    sessions_dir: &Path,
+    catalog_path: &Path,
+    cancel: &AtomicBool,
+    on_progress: impl FnMut(CatalogProgress),
+) -> Result<bool, String> {
+    let (previous, dirty) = match slot.take() {
+        Some(loaded) => (loaded.catalog, loaded.dirty),
+        None => (read_catalog(catalog_path), false),
+    };
+    let (catalog, changed) = reconcile(previous, sessions_dir, cancel, on_progress);
+    let complete = !cancel.load(Ordering::Relaxed);
+    let mut dirty = dirty || changed;
+    if dirty {
+        match write_catalog(catalog_path, &catalog) {
+            Ok(()) => dirty = false,
+            // Derived data; the next save tries again.
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    *slot = Some(LoadedCatalog {
+        catalog,
+        dirty,
+        complete,
+    });
+    Ok(complete)
+}
+
+fn refresh_default(slot: &mut Option<LoadedCatalog>) -> Result<(), String> {
+    refresh_locked(
+        slot,
+        &get_sessions_path()?,
+        &get_catalog_path()?,
+        &AtomicBool::new(false),
+        |_| {},
+    )
+    .map(|_| ())
+}
+
+/// Brings the catalog up to date with the sessions directory in one walk,
+/// whether or not the watcher is keeping it current.
+pub fn refresh_catalog() -> Result<(), String> {
+    refresh_default(&mut *lock_catalog()?)
+}
+
+/// `refresh_catalog` over `sessions_dir`, persisted to `catalog_path`, that
+/// reports every file as it becomes known and can be cancelled. Files read
+/// before the cancellation are kept in the catalog. Returns `false` if it
+/// was cancelled.
+pub fn refresh_catalog_with(
+    sessions_dir: &Path,
+    catalog_path: &Path,
+    cancel: &AtomicBool,
+    on_progress: impl FnMut(CatalogProgress),
+) -> Result<bool, String> {
+    refresh_locked(
+        &mut *lock_catalog()?,
+        sessions_dir,
+        catalog_path,
+        cancel,
+        on_progress,
+    )
+}
+
+/// Runs `f` on the in-memory catalog. While the session watcher keeps it
+/// current this costs nothing beyond `f`; otherwise, or before the first
+/// complete refresh, the sessions directory is walked first.
+pub fn with_catalog<T>(f: impl FnOnce(&SessionCatalog) -> T) -> Result<T, String> {
+    let mut slot = lock_catalog()?;
+    let live = slot.as_ref().is_some_and(|loaded| loaded.complete) && is_watching();
+    if !live {
+        refresh_default(&mut slot)?;
+    }
+    let loaded = slot.as_ref().ok_or("Session catalog is not loaded")?;
+    Ok(f(&loaded.catalog))
+}
+
+/// Writes the in-memory catalog to disk if it has changed since it was last
+/// written. Refreshes write on their own; updates from the watcher wait for this.
+pub fn save_catalog() -> Result<(), String> {
+    let mut slot = lock_catalog()?;
+    let Some(loaded) = slot.as_mut().filter(|loaded| loaded.dirty) else {
+        return Ok(());
+    };
+    write_catalog(&get_catalog_path()?, &loaded.catalog)?;
+    loaded.dirty = false;
+    Ok(())
+}
+
+/// Forgets what the catalog knows about a project's rollouts and reads them
+/// again.
+pub fn rebuild_project_catalog(project_path: &str) -> Result<(), String> {
+    let mut slot = lock_catalog()?;
+    if let Some(loaded) = slot.as_mut() {
+        loaded
+            .catalog
+            .entries
+            .retain(|_, entry| entry.cwd.as_deref() != Some(project_path));
+    }
+    refresh_default(&mut slot)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogChange {
+    Added,
+    Updated,
+    /// Moved from `previous_path` without being read again.
+    Renamed,
+    Removed,
+}
+
+/// A rollout whose catalog entry was added, replaced, moved or dropped.
+#[derive(Debug, Clone)]
+pub struct CatalogUpdate {
+    pub path: String,
+    pub change: CatalogChange,
+    /// Where a renamed file used to be.
+    pub previous_path: Option<String>,
+    /// Project the file belonged to before the change.
+    pub previous_cwd: Option<String>,
+    /// The new entry; `None` when removed.
+    pub entry: Option<CatalogEntry>,
+}
+
+impl CatalogUpdate {
+    fn new(path: String, change: CatalogChange, previous: Option<&CatalogEntry>) -> Self {
+        CatalogUpdate {
+            path,
+            change,
+            previous_path: None,
+            previous_cwd: previous.and_then(|entry| entry.cwd.clone()),
+            entry: None,
+        }
+    }
+}
+
+/// Brings the entries of `paths` up to date. A path that vanished and one
+/// that appeared with its inode, size and mtime are reported as a rename,
+/// the same way `reconcile` matches them.
+fn update_entries(catalog: &mut SessionCatalog, paths: &[PathBuf]) -> Vec<CatalogUpdate> {
+    let mut updates = Vec::new();
+    let mut vanished = Vec::new();
+    let mut appeared = Vec::new();
+    let mut to_read = Vec::new();
+    for path in paths {
+        let key = path.to_string_lossy().to_string();
+        let current = std::fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.is_file());
+        match (catalog.entries.get(&key), current) {
+            (Some(previous), Some(metadata)) if previous.is_current(&metadata) => {}
+            (Some(_), None) => vanished.push(key),
+            (None, Some(metadata)) => appeared.push((key, metadata)),
+            (Some(_), Some(_)) => to_read.push(key),
+            (None, None) => {}
+        }
+    }
+
+    for (key, metadata) in appeared {
+        let old_key = vanished.iter().position(|old| {
+            catalog.entries[old].inode.is_some()
+                && catalog.entries[old].fingerprint() == fingerprint(&metadata)
+        });
+        let Some(old_key) = old_key.map(|index| vanished.swap_remove(index)) else {
+            to_read.push(key);
+            continue;
+        };
+        let Some(mut entry) = catalog.entries.remove(&old_key) else {
+            continue;
+        };
+        if let Some(session) = &mut entry.session {
+            session.path = key.clone();
+        }
+        let mut update = CatalogUpdate::new(key.clone(), CatalogChange::Renamed, Some(&entry));
+        update.previous_path = Some(old_key);
+        update.entry = Some(entry.clone());
+        catalog.entries.insert(key, entry);
+        updates.push(update);
+    }
+
+    for key in vanished {
+        let previous = catalog.entries.remove(&key);
+        updates.push(CatalogUpdate::new(
+            key,
+            CatalogChange::Removed,
+            previous.as_ref(),
+        ));
+    }
+
+    for key in to_read {
+        let previous = catalog.entries.remove(&key);
+        match CatalogEntry::read(Path::new(&key)) {
+            Some(entry) => {
+                let change = if previous.is_some() {
+                    CatalogChange::Updated
+                } else {
+                    CatalogChange::Added
+                };
+                let mut update = CatalogUpdate::new(key.clone(), change, previous.as_ref());
+                update.entry = Some(entry.clone());
+                catalog.entries.insert(key, entry);
+                updates.push(update);
+            }
+            None if previous.is_some() => updates.push(CatalogUpdate::new(
+                key,
+                CatalogChange::Removed,
+                previous.as_ref(),
+            )),
+            None => {}
+        }
+    }
+    updates
+}
+
+/// Rollouts the in-memory catalog knows under `dir`, without looking at the
+/// disk; for finding what was in a directory that is gone.
+pub fn catalog_paths_under(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    Ok(lock_catalog()?
+        .as_ref()
+        .map(|loaded| loaded.catalog.paths_under(dir))
+        .unwrap_or_default())
+}
+
+/// Makes the next use of the catalog walk the sessions directory again,
+/// e.g. after the watcher missed events.
+pub fn invalidate_catalog() -> Result<(), String> {
+    if let Some(loaded) = lock_catalog()?.as_mut() {
+        loaded.complete = false;
+    }
+    Ok(())
+}
+
+/// Re-reads just `paths`, for callers that already know what changed on
+/// disk. Files that are still current produce no update. Only the in-memory
+/// catalog changes; `save_catalog` writes it out.
+pub fn update_catalog_paths(paths: &[PathBuf]) -> Result<Vec<CatalogUpdate>, String> {
+    let mut slot = lock_catalog()?;
+    let loaded = match slot.take() {
+        Some(loaded) => loaded,
+        // Compared against the catalog on disk; the first use walks the
+        // sessions directory for everything else.
+        None => LoadedCatalog {
+            catalog: read_catalog(&get_catalog_path()?),
+            dirty: false,
+            complete: false,
+        },
+    };
+    let loaded = slot.insert(loaded);
+    let updates = update_entries(&mut loaded.catalog, paths);
+    loaded.dirty |= !updates.is_empty();
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    fn write_rollout(path: &Path, id: &str) {
+        std::fs::write(path, ROLLOUT.replace("0199-abc", id)).unwrap();
+    }
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    fn scan(previous: SessionCatalog, dir: &Path) -> (SessionCatalog, bool) {
+        reconcile(previous, dir, &AtomicBool::new(false), |_| {})
+    }
+
+    fn key(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    /// Marks an entry so the tests can tell whether it was read again.
+    fn mark(catalog: &mut SessionCatalog, path: &Path) {
+        let entry = catalog.entries.get_mut(&key(path)).unwrap();
+        entry.session.as_mut().unwrap().preview = "marked".to_string();
+    }
+
+    fn preview(catalog: &SessionCatalog, path: &Path) -> String {
+        catalog.entries[&key(path)]
+            .session
+            .as_ref()
+            .unwrap()
+            .preview
+            .clone()
+    }
+
+    #[test]
+    fn unchanged_files_are_not_read_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        write_rollout(&a, "0199-a");
+
+        let (mut catalog, changed) = scan(SessionCatalog::default(), dir.path());
+        assert!(changed);
+        assert_eq!(catalog.entries_by_project().len(), 1);
+        mark(&mut catalog, &a);
+
+        let (catalog, changed) = scan(catalog, dir.path());
+        assert!(!changed);
+        assert_eq!(preview(&catalog, &a), "marked");
+    }
+
+    #[test]
+    fn deleted_files_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        write_rollout(&a, "0199-a");
+        write_rollout(&b, "0199-b");
+        let (catalog, _) = scan(SessionCatalog::default(), dir.path());
+
+        std::fs::remove_file(&a).unwrap();
+        let (catalog, changed) = scan(catalog, dir.path());
+        assert!(changed);
+        assert_eq!(catalog.entries.keys().collect::<Vec<_>>(), vec![&key(&b)]);
+    }
+
+    #[test]
+    fn renamed_files_keep_their_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let moved = dir.path().join("sub").join("moved.jsonl");
+        write_rollout(&a, "0199-a");
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+        mark(&mut catalog, &a);
+
+        std::fs::create_dir(moved.parent().unwrap()).unwrap();
+        std::fs::rename(&a, &moved).unwrap();
+        let (catalog, changed) = scan(catalog, dir.path());
+        assert!(changed);
+        assert!(!catalog.entries.contains_key(&key(&a)));
+        assert_eq!(preview(&catalog, &moved), "marked");
+        let session = catalog.entries[&key(&moved)].session.as_ref().unwrap();
+        assert_eq!(session.path, key(&moved));
+    }
+
+    #[test]
+    fn same_size_replacement_is_read_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        write_rollout(&a, "0199-a");
+        let modified = std::fs::metadata(&a).unwrap().modified().unwrap();
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+        mark(&mut catalog, &a);
+
+        // Same length and mtime, but a new file in place of the old one.
+        let replacement = dir.path().join("a.tmp");
+        write_rollout(&replacement, "0199-b");
+        set_modified(&replacement, modified);
+        std::fs::rename(&replacement, &a).unwrap();
+
+        let (catalog, changed) = scan(catalog, dir.path());
+        assert!(changed);
+        let session = catalog.entries[&key(&a)].session.as_ref().unwrap();
+        assert_eq!(session.conversation_id, "0199-b");
+        assert_ne!(session.preview, "marked");
+    }
+
+    #[test]
+    fn back_dated_copies_are_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let copy = dir.path().join("copy.jsonl");
+        write_rollout(&a, "0199-a");
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+        mark(&mut catalog, &a);
+
+        // A copy with the original's mtime is a different file, not a rename.
+        std::fs::copy(&a, &copy).unwrap();
+        set_modified(&copy, std::fs::metadata(&a).unwrap().modified().unwrap());
+        set_modified(&a, SystemTime::now() - Duration::from_secs(86_400));
+
+        let (catalog, changed) = scan(catalog, dir.path());
+        assert!(changed);
+        assert_eq!(catalog.entries.len(), 2);
+        assert_ne!(preview(&catalog, &copy), "marked");
+        assert_ne!(preview(&catalog, &a), "marked");
+    }
+
+    #[test]
+    fn cancelled_scan_keeps_known_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        write_rollout(&a, "0199-a");
+        let (catalog, _) = scan(SessionCatalog::default(), dir.path());
+
+        write_rollout(&b, "0199-b");
+        let (catalog, _) = reconcile(catalog, dir.path(), &AtomicBool::new(true), |_| {});
+        assert!(catalog.entries.contains_key(&key(&a)));
+        assert!(!catalog.entries.contains_key(&key(&b)));
+    }
+
+    #[test]
+    fn updates_report_renames() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        let moved = dir.path().join("moved.jsonl");
+        write_rollout(&a, "0199-a");
+        write_rollout(&b, "0199-b");
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+        mark(&mut catalog, &a);
+
+        std::fs::rename(&a, &moved).unwrap();
+        std::fs::remove_file(&b).unwrap();
+        let updates = update_entries(&mut catalog, &[a.clone(), moved.clone(), b.clone()]);
+        let changes: Vec<(CatalogChange, &str, Option<&str>)> = updates
+            .iter()
+            .map(|u| (u.change, u.path.as_str(), u.previous_path.as_deref()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    CatalogChange::Renamed,
+                    key(&moved).as_str(),
+                    Some(key(&a).as_str())
+                ),
+                (CatalogChange::Removed, key(&b).as_str(), None),
+            ]
+        );
+        assert_eq!(preview(&catalog, &moved), "marked");
+
+        // Nothing changed since.
+        assert!(update_entries(&mut catalog, &[moved]).is_empty());
+    }
+
+    #[test]
+    fn updates_read_new_and_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        write_rollout(&a, "0199-a");
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+
+        write_rollout(&b, "0199-b");
+        std::fs::write(
+            &a,
+            format!("{}\n{{}}\n", ROLLOUT.replace("0199-abc", "0199-a")),
+        )
+        .unwrap();
+        let updates = update_entries(&mut catalog, &[a.clone(), b.clone()]);
+        let changes: Vec<CatalogChange> = updates.iter().map(|u| u.change).collect();
+        assert_eq!(changes, vec![CatalogChange::Updated, CatalogChange::Added]);
+        assert_eq!(updates[0].previous_cwd.as_deref(), Some("/home/me/app"));
+        assert_eq!(catalog.entries.len(), 2);
+    }
+
+    #[test]
+    fn removed_directories_drop_their_rollouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = dir.path().join("2025").join("09").join("20");
+        let other_day = dir.path().join("2025").join("09").join("2");
+        std::fs::create_dir_all(&day).unwrap();
+        std::fs::create_dir_all(&other_day).unwrap();
+        let a = day.join("a.jsonl");
+        let b = other_day.join("b.jsonl");
+        write_rollout(&a, "0199-a");
+        write_rollout(&b, "0199-b");
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+
+        std::fs::remove_dir_all(&day).unwrap();
+        let under = catalog.paths_under(&day);
+        assert_eq!(under, vec![a.clone()]);
+        let updates = update_entries(&mut catalog, &under);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].change, CatalogChange::Removed);
+        assert_eq!(catalog.entries.keys().collect::<Vec<_>>(), vec![&key(&b)]);
+    }
+
+    #[test]
+    fn unreadable_or_outdated_catalogs_start_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let catalog_path = dir.path().join(CATALOG_FILE_NAME);
+        std::fs::write(&catalog_path, "{\"entries\": [").unwrap();
+        assert!(read_catalog(&catalog_path).entries.is_empty());
+
+        let a = dir.path().join("a.jsonl");
+        write_rollout(&a, "0199-a");
+        let (mut catalog, _) = scan(SessionCatalog::default(), dir.path());
+        write_catalog(&catalog_path, &catalog).unwrap();
+        assert_eq!(read_catalog(&catalog_path).entries.len(), 1);
+
+        catalog.schema_version = CATALOG_SCHEMA_VERSION - 1;
+        write_catalog(&catalog_path, &catalog).unwrap();
+        assert!(read_catalog(&catalog_path).entries.is_empty());
+    }
+
+    #[test]
+    fn title_updates_survive_concurrent_catalog_refreshes() {
+        use crate::session_files::metadata::{load_metadata_at, update_metadata_at};
+        use crate::session_files::update::set_title;
+
+        let sessions = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        for n in 0..5 {
+            write_rollout(
+                &sessions.path().join(format!("{}.jsonl", n)),
+                &format!("0199-{}", n),
+            );
+        }
+        let catalog_path = data.path().join("session_catalog.json");
+        let metadata_path = data.path().join("session_metadata.json");
+
+        let mut handles = Vec::new();
+        for worker in 0..4 {
+            let metadata_path = metadata_path.clone();
+            handles.push(std::thread::spawn(move || {
+                for n in 0..10 {
+                    update_metadata_at(&metadata_path, |store| {
+                        set_title(store, format!("{}-{}", worker, n), format!("title {}", n));
+                        Ok(())
+                    })
+                    .unwrap();
+                }
+            }));
+        }
+        for _ in 0..2 {
+            let sessions_dir = sessions.path().to_path_buf();
+            let catalog_path = catalog_path.clone();
+            handles.push(std::thread::spawn(move || {
+                let mut slot = None;
+                for _ in 0..5 {
+                    let refreshed = refresh_locked(
+                        &mut slot,
+                        &sessions_dir,
+                        &catalog_path,
+                        &AtomicBool::new(false),
+                        |_| {},
+                    );
+                    assert!(refreshed.unwrap());
+                    // Drop the in-memory copy so the next refresh rewrites the file.
+                    slot = None;
+                    std::fs::remove_file(&catalog_path).ok();
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let store = load_metadata_at(&metadata_path).unwrap();
+        assert_eq!(store.sessions.len(), 40);
+        assert_eq!(store.sessions["3-9"].title.as_deref(), Some("title 9"));
+        let leftovers: Vec<_> = std::fs::read_dir(data.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+    }
+}
@@ -0,0 +1,529 @@
+use chrono::{DateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Normalized event kinds, see `docs/session_event.schema.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    User,
+    Assistant,
+    ToolCall,
+    ToolResult,
+    Error,
+    Meta,
+}
+
+/// A single rollout line normalized into the shape documented in
+/// `docs/session_event.schema.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEvent {
+    pub id: String,
+    pub kind: EventKind,
+    /// The rollout record type, e.g. `user_message`, `function_call` or `session_meta`.
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(rename = "rawJSON")]
+    pub raw_json: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_output: Option<String>,
+    #[serde(rename = "messageID", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(rename = "parentID", skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_delta: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(rename = "encrypted_content", skip_serializing_if = "Option::is_none")]
+    pub encrypted_content: Option<String>,
+}
+
+impl SessionEvent {
+    fn new(kind: EventKind, event_type: &str, index: usize, raw_json: &str) -> Self {
+        SessionEvent {
+            id: format!("{}-{}", event_type, index),
+            kind,
+            event_type: event_type.to_string(),
+            raw_json: raw_json.to_string(),
+            timestamp: None,
+            role: None,
+            text: None,
+            tool_name: None,
+            tool_input: None,
+            tool_output: None,
+            message_id: None,
+            parent_id: None,
+            is_delta: None,
+            model: None,
+            encrypted_content: None,
+        }
+    }
+}
+
+fn non_empty(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// A record timestamp as RFC 3339. Numeric timestamps are epoch seconds,
+/// milliseconds or microseconds, told apart by their magnitude.
+fn parse_event_timestamp(value: &Value) -> Option<String> {
+    if value.is_string() {
+        return non_empty(value);
+    }
+    let epoch = value.as_f64()?;
+    let micros = match epoch.abs() {
+        e if e < 1e11 => epoch * 1e6,
+        e if e < 1e14 => epoch * 1e3,
+        _ => epoch,
+    };
+    DateTime::from_timestamp_micros(micros as i64)
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+/// Turns `function_call` arguments into something readable: shell calls are
+/// reduced to their joined `command`, everything else keeps the raw arguments.
+fn format_tool_input(arguments: &str) -> String {
+    if let Ok(args) = serde_json::from_str::<Value>(arguments) {
+        if let Some(command) = args["command"].as_array() {
+            let parts: Vec<&str> = command.iter().filter_map(|p| p.as_str()).collect();
+            return parts.join(" ");
+        }
+    }
+    arguments.to_string()
+}
+
+/// Tool outputs are usually a JSON string `{"output": "...", "metadata": {...}}`.
+fn format_tool_output(output: &Value) -> Option<String> {
+    let raw = output.as_str()?;
+    match serde_json::from_str::<Value>(raw) {
+        Ok(inner) => match inner["output"].as_str() {
+            Some(text) => Some(text.to_string()),
+            None => Some(raw.to_string()),
+        },
+        Err(_) => Some(raw.to_string()),
+    }
+}
+
+/// The `payload` object of the record an event was parsed from.
+pub fn raw_payload(event: &SessionEvent) -> Value {
+    serde_json::from_str::<Value>(&event.raw_json)
+        .map(|mut value| value["payload"].take())
+        .unwrap_or(Value::Null)
+}
+
+/// Stateful line parser. Keeps track of the active model from `turn_context`
+/// and of tool call ids so results can be attributed to the tool that produced them.
+#[derive(Default)]
+pub struct EventParser {
+    index: usize,
+    model: Option<String>,
+    tool_names: HashMap<String, String>,
+}
+
+impl EventParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses one rollout line. Returns `None` for blank or unparseable lines,
+    /// which are common in rollouts cut off mid-write, and for records that
+    /// duplicate another event (e.g. `response_item` messages,
+    /// which are mirrored by `event_msg` user/agent messages).
+    pub fn parse_line(&mut self, line: &str) -> Option<SessionEvent> {
+        let index = self.index;
+        self.index += 1;
+
+        let cleaned = if line.contains('\0') {
+            line.replace('\0', "")
+        } else {
+            line.to_string()
+        };
+        if cleaned.trim().is_empty() {
+            return None;
+        }
+        let value: Value = serde_json::from_str(&cleaned).ok()?;
+
+        let payload = &value["payload"];
+        let mut event = match value["type"].as_str().unwrap_or_default() {
+            "session_meta" => {
+                let mut event = SessionEvent::new(EventKind::Meta, "session_meta", index, &cleaned);
+                event.message_id = non_empty(&payload["id"]);
+                event.text = non_empty(&payload["instructions"]);
+                event
+            }
+            "turn_context" => {
+                if let Some(model) = non_empty(&payload["model"]) {
+                    self.model = Some(model);
+                }
+                SessionEvent::new(EventKind::Meta, "turn_context", index, &cleaned)
+            }
+            "event_msg" => self.parse_event_msg(payload, index, &cleaned)?,
+            "response_item" => self.parse_response_item(payload, index, &cleaned)?,
+            _ => return None,
+        };
+
+        event.timestamp = parse_event_timestamp(&value["timestamp"]);
+        event.model = self.model.clone();
+        Some(event)
+    }
+
+    fn parse_event_msg(&self, payload: &Value, index: usize, raw: &str) -> Option<SessionEvent> {
+        let msg_type = payload["type"].as_str()?;
+        let event = match msg_type {
+            "user_message" => {
+                let mut event = SessionEvent::new(EventKind::User, msg_type, index, raw);
+                event.role = Some("user".to_string());
+                // Kept even when empty: it still marks the start of a turn.
+                event.text = Some(non_empty(&payload["message"]).unwrap_or_default());
+                event
+            }
+            "agent_message" | "agent_reasoning_raw_content" => {
+                let mut event = SessionEvent::new(EventKind::Assistant, msg_type, index, raw);
+                event.role = Some("assistant".to_string());
+                event.text =
+                    Some(non_empty(&payload["message"]).or_else(|| non_empty(&payload["text"]))?);
+                event
+            }
+            "agent_reasoning" => {
+                let mut event = SessionEvent::new(EventKind::Assistant, msg_type, index, raw);
+                event.role = Some("reasoning".to_string());
+                event.text = Some(non_empty(&payload["text"])?);
+                event
+            }
+            "agent_message_delta" | "agent_reasoning_delta" => {
+                let mut event = SessionEvent::new(EventKind::Assistant, msg_type, index, raw);
+                event.role = Some("assistant".to_string());
+                event.text = payload["delta"].as_str().map(str::to_string);
+                event.is_delta = Some(true);
+                event
+            }
+            "error" | "stream_error" => {
+                let mut event = SessionEvent::new(EventKind::Error, msg_type, index, raw);
+                event.text = non_empty(&payload["message"]);
+                event
+            }
+            _ => SessionEvent::new(EventKind::Meta, msg_type, index, raw),
+        };
+        Some(event)
+    }
+
+    fn parse_response_item(
+        &mut self,
+        payload: &Value,
+        index: usize,
+        raw: &str,
+    ) -> Option<SessionEvent> {
+        let item_type = payload["type"].as_str()?;
+        let event = match item_type {
+            "function_call" | "custom_tool_call" | "local_shell_call" => {
+                let mut event = SessionEvent::new(EventKind::ToolCall, item_type, index, raw);
+                let name = non_empty(&payload["name"]).unwrap_or_else(|| "shell".to_string());
+                event.tool_input = if item_type == "function_call" {
+                    payload["arguments"].as_str().map(format_tool_input)
+                } else if item_type == "custom_tool_call" {
+                    payload["input"].as_str().map(str::to_string)
+                } else {
+                    payload["action"]["command"].as_array().map(|parts| {
+                        parts
+                            .iter()
+                            .filter_map(|p| p.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                };
+                event.message_id = non_empty(&payload["call_id"]);
+                if let Some(call_id) = &event.message_id {
+                    self.tool_names.insert(call_id.clone(), name.clone());
+                }
+                event.tool_name = Some(name);
+                event
+            }
+            "function_call_output" | "custom_tool_call_output" => {
+                let mut event = SessionEvent::new(EventKind::ToolResult, item_type, index, raw);
+                event.tool_output = format_tool_output(&payload["output"]);
+                event.parent_id = non_empty(&payload["call_id"]);
+                event.tool_name = event
+                    .parent_id
+                    .as_ref()
+                    .and_then(|id| self.tool_names.get(id).cloned());
+                event
+            }
+            "reasoning" => {
+                // Reasoning summaries are mirrored by `agent_reasoning` events; only
+                // keep the opaque blob so it is not lost entirely.
+                let encrypted = non_empty(&payload["encrypted_content"])?;
+                let mut event = SessionEvent::new(EventKind::Meta, item_type, index, raw);
+                event.encrypted_content = Some(encrypted);
+                event
+            }
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+/// Streams the events of a rollout one line at a time. A line that is not
+/// UTF-8 is reported as an error and reading goes on after it; any other read
+/// error ends the stream.
+pub struct EventStream<R> {
+    lines: std::io::Lines<R>,
+    parser: EventParser,
+    failed: bool,
+}
+
+impl<R: BufRead> Iterator for EventStream<R> {
+    type Item = Result<SessionEvent, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => {
+                    self.failed = e.kind() != std::io::ErrorKind::InvalidData;
+                    return Some(Err(format!("Failed to read session line: {}", e)));
+                }
+            };
+            if let Some(event) = self.parser.parse_line(&line) {
+                return Some(Ok(event));
+            }
+        }
+    }
+}
+
+pub fn parse_events<R: BufRead>(reader: R) -> EventStream<R> {
+    EventStream {
+        lines: reader.lines(),
+        parser: EventParser::new(),
+        failed: false,
+    }
+}
+
+pub fn open_session_events<P: AsRef<Path>>(
+    file_path: P,
+) -> Result<EventStream<BufReader<File>>, String> {
+    let file = File::open(&file_path)
+        .map_err(|e| format!("Failed to open file {:?}: {}", file_path.as_ref(), e))?;
+    Ok(parse_events(BufReader::new(file)))
+}
+
+pub fn read_session_events<P: AsRef<Path>>(file_path: P) -> Result<Vec<SessionEvent>, String> {
+    open_session_events(file_path)?.collect()
+}
+
+#[tauri::command]
+pub async fn get_session_events(session_path: String) -> Result<Vec<SessionEvent>, String> {
+    read_session_events(&session_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    fn fixture_events() -> Vec<SessionEvent> {
+        parse_events(ROLLOUT.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn find<'a>(events: &'a [SessionEvent], event_type: &str) -> Vec<&'a SessionEvent> {
+        events
+            .iter()
+            .filter(|event| event.event_type == event_type)
+            .collect()
+    }
+
+    #[test]
+    fn fixture_kinds_in_order() {
+        let events = fixture_events();
+        let kinds: Vec<(EventKind, &str)> = events
+            .iter()
+            .map(|event| (event.kind, event.event_type.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (EventKind::Meta, "session_meta"),
+                (EventKind::User, "user_message"),
+                (EventKind::Meta, "turn_context"),
+                (EventKind::Assistant, "agent_reasoning"),
+                (EventKind::ToolCall, "function_call"),
+                (EventKind::ToolResult, "function_call_output"),
+                (EventKind::ToolCall, "custom_tool_call"),
+                (EventKind::ToolResult, "custom_tool_call_output"),
+                (EventKind::Meta, "turn_context"),
+                (EventKind::ToolCall, "local_shell_call"),
+                (EventKind::ToolResult, "function_call_output"),
+                (EventKind::Error, "stream_error"),
+                (EventKind::Assistant, "agent_message"),
+                (EventKind::Error, "error"),
+            ]
+        );
+    }
+
+    #[test]
+    fn session_meta_and_messages() {
+        let events = fixture_events();
+        let meta = find(&events, "session_meta")[0];
+        assert_eq!(meta.id, "session_meta-0");
+        assert_eq!(meta.message_id.as_deref(), Some("0199-abc"));
+        assert_eq!(meta.text.as_deref(), Some("Be concise."));
+
+        // The `response_item` copy of the user message is dropped.
+        let user = find(&events, "user_message");
+        assert_eq!(user.len(), 1);
+        assert_eq!(user[0].role.as_deref(), Some("user"));
+        assert_eq!(
+            user[0].text.as_deref(),
+            Some("Fix the migration bug in db.rs")
+        );
+
+        let reasoning = find(&events, "agent_reasoning")[0];
+        assert_eq!(reasoning.role.as_deref(), Some("reasoning"));
+        assert_eq!(reasoning.text.as_deref(), Some("**Inspecting db.rs**"));
+
+        let agent = find(&events, "agent_message")[0];
+        assert_eq!(agent.role.as_deref(), Some("assistant"));
+        assert_eq!(agent.text.as_deref(), Some("Fixed the migration."));
+
+        let errors: Vec<_> = events
+            .iter()
+            .filter(|event| event.kind == EventKind::Error)
+            .filter_map(|event| event.text.as_deref())
+            .collect();
+        assert_eq!(errors, vec!["Reconnecting... 1/5", "Usage limit reached"]);
+    }
+
+    #[test]
+    fn model_follows_turn_context() {
+        let events = fixture_events();
+        assert_eq!(find(&events, "user_message")[0].model, None);
+        assert_eq!(
+            find(&events, "function_call")[0].model.as_deref(),
+            Some("gpt-5-codex")
+        );
+        assert_eq!(
+            find(&events, "local_shell_call")[0].model.as_deref(),
+            Some("gpt-5")
+        );
+    }
+
+    #[test]
+    fn tool_calls_and_outputs() {
+        let events = fixture_events();
+
+        let shell = find(&events, "function_call")[0];
+        assert_eq!(shell.tool_name.as_deref(), Some("shell"));
+        assert_eq!(shell.tool_input.as_deref(), Some("bash -lc cat db.rs"));
+        assert_eq!(shell.message_id.as_deref(), Some("call_1"));
+
+        let patch = find(&events, "custom_tool_call")[0];
+        assert_eq!(patch.tool_name.as_deref(), Some("apply_patch"));
+        assert!(patch
+            .tool_input
+            .as_deref()
+            .is_some_and(|input| input.starts_with("*** Begin Patch")));
+
+        let local = find(&events, "local_shell_call")[0];
+        assert_eq!(local.tool_name.as_deref(), Some("shell"));
+        assert_eq!(local.tool_input.as_deref(), Some("cargo test"));
+
+        let outputs: Vec<_> = events
+            .iter()
+            .filter(|event| event.kind == EventKind::ToolResult)
+            .map(|event| {
+                (
+                    event.parent_id.as_deref(),
+                    event.tool_name.as_deref(),
+                    event.tool_output.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outputs,
+            vec![
+                (Some("call_1"), Some("shell"), Some("fn migrate() {}\n")),
+                (
+                    Some("call_2"),
+                    Some("apply_patch"),
+                    Some("Success. Updated the following files:\nM db.rs\n")
+                ),
+                (Some("call_3"), Some("shell"), Some("plain text output")),
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_and_broken_lines_are_skipped() {
+        let mut parser = EventParser::new();
+        assert!(parser.parse_line("").is_none());
+        assert!(parser.parse_line("not json").is_none());
+        let event = parser
+            .parse_line(
+                r#"{"type":"event_msg","payload":{"type":"agent_message","message":"hi\u0000"}}"#,
+            )
+            .unwrap();
+        // Ids count every line, so they stay stable when lines are skipped.
+        assert_eq!(event.id, "agent_message-2");
+    }
+
+    #[test]
+    fn numeric_timestamps_become_rfc3339() {
+        let mut parser = EventParser::new();
+        let mut timestamp_of = |timestamp: &str| {
+            let line = format!(
+                r#"{{"timestamp":{},"type":"event_msg","payload":{{"type":"agent_message","message":"hi"}}}}"#,
+                timestamp
+            );
+            parser.parse_line(&line).unwrap().timestamp
+        };
+        let expected = Some("2025-09-20T10:00:00Z".to_string());
+        assert_eq!(timestamp_of("1758362400"), expected);
+        assert_eq!(timestamp_of("1758362400000"), expected);
+        assert_eq!(timestamp_of("1758362400000000"), expected);
+        assert_eq!(
+            timestamp_of("1758362400.5"),
+            Some("2025-09-20T10:00:00.500Z".to_string())
+        );
+        assert_eq!(
+            timestamp_of("\"2025-09-20T10:00:00.000Z\""),
+            Some("2025-09-20T10:00:00.000Z".to_string())
+        );
+        assert_eq!(timestamp_of("\"\""), None);
+        assert_eq!(timestamp_of("null"), None);
+    }
+
+    #[test]
+    fn empty_user_messages_are_kept() {
+        let mut parser = EventParser::new();
+        let event = parser
+            .parse_line(r#"{"type":"event_msg","payload":{"type":"user_message","message":"  "}}"#)
+            .unwrap();
+        assert_eq!(event.kind, EventKind::User);
+        assert_eq!(event.text.as_deref(), Some(""));
+    }
+}
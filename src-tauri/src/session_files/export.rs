@@ -0,0 +1,352 @@
+use super::events::{raw_payload, read_session_events, EventKind, SessionEvent};
+use super::metadata::load_metadata;
+use super::redact::{load_redaction_config, read_redacted_events, RedactionReport, Redactor};
+use super::store::write_atomic;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExportOptions {
+    pub include_reasoning: bool,
+    /// Tool outputs longer than this many characters are cut.
+    pub max_output_chars: usize,
+    /// Run the rollout through the redaction rules before rendering.
+    pub redact: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            include_reasoning: false,
+            max_output_chars: 2000,
+            redact: false,
+        }
+    }
+}
+
+/// Header fields taken from `session_meta`, plus the title from the metadata
+/// store.
+#[derive(Debug, Clone, Default)]
+pub struct SessionHeader {
+    pub conversation_id: String,
+    pub title: Option<String>,
+    pub cwd: Option<String>,
+    pub started_at: Option<String>,
+    pub cli_version: Option<String>,
+    pub model: Option<String>,
+    pub instructions: Option<String>,
+}
+
+impl SessionHeader {
+    pub fn from_events(events: &[SessionEvent]) -> Self {
+        let mut header = SessionHeader::default();
+        if let Some(meta) = events.iter().find(|e| e.event_type == "session_meta") {
+            let payload = raw_payload(meta);
+            header.conversation_id = meta.message_id.clone().unwrap_or_default();
+            header.instructions = meta.text.clone();
+            header.cwd = payload["cwd"].as_str().map(str::to_string);
+            header.started_at = payload["timestamp"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| meta.timestamp.clone());
+            header.cli_version = payload["cli_version"].as_str().map(str::to_string);
+        }
+        header.model = events.iter().rev().find_map(|e| e.model.clone());
+        header
+    }
+
+    pub fn display_title(&self) -> String {
+        match &self.title {
+            Some(title) => title.clone(),
+            None => format!("Codex session {}", self.conversation_id),
+        }
+    }
+}
+
+/// What a tool call did, as far as an export cares.
+pub enum ToolInvocation {
+    /// A shell command, with the `bash -lc` wrapper removed.
+    Shell(String),
+    /// The body of an `apply_patch` call.
+    Patch(String),
+    Other(String),
+}
+
+impl ToolInvocation {
+    pub fn from_event(event: &SessionEvent) -> Self {
+        let payload = raw_payload(event);
+        if event.tool_name.as_deref() == Some("apply_patch") {
+            if let Some(input) = payload["input"].as_str() {
+                return ToolInvocation::Patch(input.to_string());
+            }
+        }
+
+        let command: Vec<String> = payload["arguments"]
+            .as_str()
+            .and_then(|args| serde_json::from_str::<Value>(args).ok())
+            .and_then(|args| args["command"].as_array().cloned())
+            .or_else(|| payload["action"]["command"].as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|part| part.as_str().map(str::to_string))
+            .collect();
+        match command.as_slice() {
+            [program, patch] if program == "apply_patch" => ToolInvocation::Patch(patch.clone()),
+            [shell, flag, script] if shell.ends_with("sh") && flag.starts_with('-') => {
+                ToolInvocation::Shell(script.clone())
+            }
+            [] => ToolInvocation::Other(event.tool_input.clone().unwrap_or_default()),
+            parts => ToolInvocation::Shell(parts.join(" ")),
+        }
+    }
+}
+
+/// Drops the `*** Begin Patch` / `*** End Patch` envelope; the rest already
+/// reads as a diff.
+pub fn patch_as_diff(patch: &str) -> String {
+    patch
+        .lines()
+        .filter(|line| !matches!(line.trim(), "*** Begin Patch" | "*** End Patch"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn exit_code(event: &SessionEvent) -> Option<i64> {
+    raw_payload(event)["output"]
+        .as_str()
+        .and_then(|output| serde_json::from_str::<Value>(output).ok())
+        .and_then(|output| output["metadata"]["exit_code"].as_i64())
+}
+
+/// Cuts `text` to `max_chars` characters, noting how much was left out.
+pub fn trim_output(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.trim_end().to_string();
+    }
+    let kept: String = text.chars().take(max_chars).collect();
+    format!(
+        "{}\n… ({} more characters)",
+        kept.trim_end(),
+        total - max_chars
+    )
+}
+
+/// A fenced code block whose fence is longer than any backtick run in `body`.
+fn fence(lang: &str, body: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in body.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let ticks = "`".repeat(longest.max(2) + 1);
+    format!("{ticks}{lang}\n{}\n{ticks}\n\n", body.trim_end())
+}
+
+/// A session ready to be rendered by one of the exporters.
+pub struct ExportSession {
+    pub header: SessionHeader,
+    pub events: Vec<SessionEvent>,
+    /// What was masked, when the options ask for redaction.
+    pub report: Option<RedactionReport>,
+}
+
+/// Events and header to export, redacted when the options ask for it. The
+/// title is user-written and goes through the same rules as the rollout.
+pub fn load_export_session(
+    session_path: &str,
+    options: &ExportOptions,
+) -> Result<ExportSession, String> {
+    let title_of = |header: &SessionHeader| {
+        load_metadata()
+            .ok()
+            .and_then(|store| store.sessions.get(&header.conversation_id).cloned())
+            .and_then(|metadata| metadata.title)
+    };
+    if !options.redact {
+        let events = read_session_events(session_path)?;
+        let mut header = SessionHeader::from_events(&events);
+        header.title = title_of(&header);
+        return Ok(ExportSession {
+            header,
+            events,
+            report: None,
+        });
+    }
+
+    let redactor = Redactor::new(&load_redaction_config()?)?;
+    let mut report = RedactionReport::default();
+    let events = read_redacted_events(Path::new(session_path), &redactor, &mut report)?;
+    let mut header = SessionHeader::from_events(&events);
+    header.title = title_of(&header).map(|title| redactor.redact(&title, &mut report));
+    Ok(ExportSession {
+        header,
+        events,
+        report: Some(report),
+    })
+}
+
+pub fn render_markdown(
+    header: &SessionHeader,
+    events: &[SessionEvent],
+    options: &ExportOptions,
+) -> String {
+    let mut out = format!("# {}\n\n", header.display_title());
+    let fields = [
+        ("Session", Some(&header.conversation_id)),
+        ("Project", header.cwd.as_ref()),
+        ("Started", header.started_at.as_ref()),
+        ("Model", header.model.as_ref()),
+        ("Codex CLI", header.cli_version.as_ref()),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            out.push_str(&format!("- **{}:** `{}`\n", label, value));
+        }
+    }
+    out.push('\n');
+
+    if let Some(instructions) = &header.instructions {
+        out.push_str("<details>\n<summary>Instructions</summary>\n\n");
+        out.push_str(&fence("text", instructions));
+        out.push_str("</details>\n\n");
+    }
+
+    for event in events {
+        if event.is_delta == Some(true) {
+            continue;
+        }
+        match event.kind {
+            EventKind::User => {
+                out.push_str("## User\n\n");
+                out.push_str(event.text.as_deref().unwrap_or_default().trim());
+                out.push_str("\n\n");
+            }
+            EventKind::Assistant if event.role.as_deref() == Some("reasoning") => {
+                if options.include_reasoning {
+                    let text = event.text.as_deref().unwrap_or_default().trim();
+                    for line in text.lines() {
+                        out.push_str(&format!("> {}\n", line));
+                    }
+                    out.push('\n');
+                }
+            }
+            EventKind::Assistant => {
+                out.push_str("## Assistant\n\n");
+                out.push_str(event.text.as_deref().unwrap_or_default().trim());
+                out.push_str("\n\n");
+            }
+            EventKind::ToolCall => {
+                let name = event.tool_name.as_deref().unwrap_or("tool");
+                out.push_str(&format!("**Tool call:** `{}`\n\n", name));
+                match ToolInvocation::from_event(event) {
+                    ToolInvocation::Shell(command) => out.push_str(&fence("sh", &command)),
+                    ToolInvocation::Patch(patch) => {
+                        out.push_str(&fence("diff", &patch_as_diff(&patch)))
+                    }
+                    ToolInvocation::Other(input) => out.push_str(&fence("json", &input)),
+                }
+            }
+            EventKind::ToolResult => {
+                let output = event.tool_output.as_deref().unwrap_or_default();
+                match exit_code(event) {
+                    Some(code) => out.push_str(&format!("Output (exit code {}):\n\n", code)),
+                    None => out.push_str("Output:\n\n"),
+                }
+                out.push_str(&fence(
+                    "text",
+                    &trim_output(output, options.max_output_chars),
+                ));
+            }
+            EventKind::Error => {
+                let text = event.text.as_deref().unwrap_or("error");
+                out.push_str(&format!("> **Error:** {}\n\n", text));
+            }
+            EventKind::Meta => {}
+        }
+    }
+
+    format!("{}\n", out.trim_end())
+}
+
+/// Writes the session as Markdown to `output_path`. Returns what was masked
+/// when `options.redact` is set.
+#[tauri::command]
+pub async fn export_session_markdown(
+    session_path: String,
+    output_path: String,
+    options: Option<ExportOptions>,
+) -> Result<Option<RedactionReport>, String> {
+    let options = options.unwrap_or_default();
+    let session = load_export_session(&session_path, &options)?;
+    let markdown = render_markdown(&session.header, &session.events, &options);
+    write_atomic(Path::new(&output_path), markdown.as_bytes())?;
+    Ok(session.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_files::events::parse_events;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    fn fixture_events() -> Vec<SessionEvent> {
+        parse_events(ROLLOUT.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn fences_outgrow_backtick_runs_in_the_body() {
+        assert_eq!(fence("sh", "ls\n"), "```sh\nls\n```\n\n");
+        assert_eq!(
+            fence("text", "use ``` to fence"),
+            "````text\nuse ``` to fence\n````\n\n"
+        );
+        assert_eq!(fence("text", "`````"), "``````text\n`````\n``````\n\n");
+    }
+
+    #[test]
+    fn long_outputs_are_cut_by_characters() {
+        assert_eq!(trim_output("short\n\n", 10), "short");
+        assert_eq!(trim_output("ééééé", 5), "ééééé");
+        assert_eq!(trim_output("éééééxyz", 5), "ééééé\n… (3 more characters)");
+        assert_eq!(trim_output("ab   cd", 4), "ab\n… (3 more characters)");
+    }
+
+    #[test]
+    fn patches_lose_their_envelope() {
+        let patch = "*** Begin Patch\n*** Update File: db.rs\n@@\n-old\n+new\n*** End Patch\n";
+        assert_eq!(
+            patch_as_diff(patch),
+            "*** Update File: db.rs\n@@\n-old\n+new"
+        );
+    }
+
+    #[test]
+    fn markdown_collapses_instructions_and_renders_tools() {
+        let events = fixture_events();
+        let mut header = SessionHeader::from_events(&events);
+        header.title = Some("Migration fix".to_string());
+        let markdown = render_markdown(&header, &events, &ExportOptions::default());
+
+        assert!(markdown.starts_with("# Migration fix\n\n"), "{}", markdown);
+        assert!(markdown.contains("- **Session:** `0199-abc`\n"));
+        assert!(markdown.contains("- **Model:** `gpt-5`\n"));
+        assert!(markdown.contains(
+            "<details>\n<summary>Instructions</summary>\n\n```text\nBe concise.\n```\n\n</details>\n"
+        ));
+        assert!(markdown.contains("**Tool call:** `shell`\n\n```sh\ncat db.rs\n```\n"));
+        assert!(markdown.contains("Output (exit code 0):\n\n```text\nfn migrate() {}\n```\n"));
+        assert!(markdown.contains("```diff\n*** Update File:"));
+        assert!(!markdown.contains("*** Begin Patch"));
+        assert!(markdown.contains("## Assistant\n\nFixed the migration.\n"));
+        assert!(markdown.contains("> **Error:** Usage limit reached\n"));
+        // Reasoning is left out unless asked for.
+        assert!(!markdown.contains("Inspecting db.rs"));
+    }
+}
@@ -0,0 +1,356 @@
+use super::events::{EventKind, SessionEvent};
+use super::export::{
+    exit_code, load_export_session, patch_as_diff, trim_output, ExportOptions, SessionHeader,
+    ToolInvocation,
+};
+use super::pricing::load_price_table;
+use super::redact::RedactionReport;
+use super::store::write_atomic;
+use super::usage::{read_turn_usage, UsageTotals};
+use chrono::DateTime;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const STYLE: &str = r#"
+body { font: 15px/1.5 -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2328; background: #fff; }
+header { border-bottom: 1px solid #d0d7de; margin-bottom: 1.5rem; }
+header dl { display: grid; grid-template-columns: max-content 1fr; gap: .25rem 1rem; }
+header dt { color: #59636e; }
+header dd { margin: 0; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+.tokens { display: flex; flex-wrap: wrap; gap: .5rem; margin: 1rem 0; }
+.tokens span { background: #f6f8fa; border: 1px solid #d0d7de; border-radius: 6px; padding: .25rem .5rem; }
+.event { margin: 1rem 0; }
+.event .meta { font-size: 12px; color: #59636e; }
+.message { border-radius: 8px; padding: .5rem 1rem; white-space: pre-wrap; }
+.user .message { background: #ddf4ff; }
+.assistant .message { background: #f6f8fa; }
+.reasoning .message { color: #59636e; font-style: italic; }
+.error .message { background: #ffebe9; color: #82071e; }
+details { border: 1px solid #d0d7de; border-radius: 8px; padding: .5rem 1rem; }
+summary { cursor: pointer; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+pre { background: #f6f8fa; border-radius: 6px; padding: .75rem; overflow-x: auto; font: 13px/1.45 ui-monospace, SFMono-Regular, Menlo, monospace; }
+.diff span { display: block; min-height: 1.45em; }
+.diff .add { color: #116329; background: #dafbe1; }
+.diff .del { color: #82071e; background: #ffebe9; }
+.diff .hunk { color: #0550ae; }
+.diff .file { font-weight: bold; }
+.failed summary { color: #82071e; }
+"#;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn highlight_diff(diff: &str) -> String {
+    let mut out = String::from("<pre class=\"diff\">");
+    for line in diff.lines() {
+        let class = if line.starts_with("***") {
+            "file"
+        } else if line.starts_with("@@") {
+            "hunk"
+        } else if line.starts_with('+') {
+            "add"
+        } else if line.starts_with('-') {
+            "del"
+        } else {
+            "context"
+        };
+        out.push_str(&format!(
+            "<span class=\"{}\">{}</span>",
+            class,
+            escape(line)
+        ));
+    }
+    out.push_str("</pre>");
+    out
+}
+
+fn time_of(event: &SessionEvent) -> String {
+    event
+        .timestamp
+        .as_deref()
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| dt.format("%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn render_header(header: &SessionHeader, totals: Option<&UsageTotals>, out: &mut String) {
+    out.push_str(&format!(
+        "<header>\n<h1>{}</h1>\n<dl>\n",
+        escape(&header.display_title())
+    ));
+    let fields = [
+        ("Session", Some(&header.conversation_id)),
+        ("Project", header.cwd.as_ref()),
+        ("Started", header.started_at.as_ref()),
+        ("Model", header.model.as_ref()),
+        ("Codex CLI", header.cli_version.as_ref()),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", label, escape(value)));
+        }
+    }
+    out.push_str("</dl>\n");
+
+    if let Some(totals) = totals {
+        let usage = &totals.usage;
+        out.push_str("<div class=\"tokens\">");
+        let figures = [
+            ("Input", usage.input_tokens),
+            ("Cached input", usage.cached_input_tokens),
+            ("Output", usage.output_tokens),
+            ("Reasoning", usage.reasoning_output_tokens),
+            ("Total", usage.total_tokens),
+        ];
+        for (label, value) in figures {
+            out.push_str(&format!("<span>{}: {}</span>", label, value));
+        }
+        // Turns on models missing from the price table are left out of the cost.
+        if totals.unpriced_tokens < usage.total_tokens {
+            out.push_str(&format!("<span>Estimated cost: ${:.2}</span>", totals.cost));
+        }
+        out.push_str("</div>\n");
+    }
+
+    if let Some(instructions) = &header.instructions {
+        out.push_str(&format!(
+            "<details>\n<summary>Instructions</summary>\n<pre>{}</pre>\n</details>\n",
+            escape(instructions)
+        ));
+    }
+    out.push_str("</header>\n");
+}
+
+fn render_message(event: &SessionEvent, class: &str, label: &str, out: &mut String) {
+    out.push_str(&format!(
+        "<section class=\"event {}\">\n<div class=\"meta\">{} {}</div>\n<div class=\"message\">{}</div>\n</section>\n",
+        class,
+        label,
+        time_of(event),
+        escape(event.text.as_deref().unwrap_or_default().trim())
+    ));
+}
+
+fn render_output(result: &SessionEvent, options: &ExportOptions, out: &mut String) {
+    let output = result.tool_output.as_deref().unwrap_or_default();
+    out.push_str(&format!(
+        "<pre>{}</pre>\n",
+        escape(&trim_output(output, options.max_output_chars))
+    ));
+}
+
+/// A tool call and its result as one collapsible block, summarised by the
+/// command or tool name.
+fn render_tool_call(
+    call: &SessionEvent,
+    result: Option<&SessionEvent>,
+    options: &ExportOptions,
+    out: &mut String,
+) {
+    let name = call.tool_name.as_deref().unwrap_or("tool");
+    let invocation = ToolInvocation::from_event(call);
+    let summary = match &invocation {
+        ToolInvocation::Shell(command) => command.lines().next().unwrap_or_default().to_string(),
+        ToolInvocation::Patch(_) => "apply_patch".to_string(),
+        ToolInvocation::Other(_) => name.to_string(),
+    };
+    let code = result.and_then(exit_code);
+    let failed = code.is_some_and(|code| code != 0);
+    let status = match code {
+        Some(code) => format!(" (exit {})", code),
+        None => String::new(),
+    };
+
+    out.push_str(&format!(
+        "<section class=\"event tool\">\n<div class=\"meta\">{} {}</div>\n<details{}>\n<summary>{}{}</summary>\n",
+        escape(name),
+        time_of(call),
+        if failed { " class=\"failed\"" } else { "" },
+        escape(&summary),
+        status
+    ));
+    match invocation {
+        ToolInvocation::Shell(command) => {
+            out.push_str(&format!("<pre>$ {}</pre>\n", escape(&command)))
+        }
+        ToolInvocation::Patch(patch) => out.push_str(&highlight_diff(&patch_as_diff(&patch))),
+        ToolInvocation::Other(input) => out.push_str(&format!("<pre>{}</pre>\n", escape(&input))),
+    }
+    if let Some(result) = result {
+        render_output(result, options, out);
+    }
+    out.push_str("</details>\n</section>\n");
+}
+
+/// `totals` is the session's per-turn usage, shown in the header when set.
+pub fn render_html(
+    header: &SessionHeader,
+    events: &[SessionEvent],
+    totals: Option<&UsageTotals>,
+    options: &ExportOptions,
+) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape(&header.display_title()),
+        STYLE
+    );
+    render_header(header, totals, &mut out);
+
+    let results: HashMap<&str, &SessionEvent> = events
+        .iter()
+        .filter(|event| event.kind == EventKind::ToolResult)
+        .filter_map(|event| Some((event.parent_id.as_deref()?, event)))
+        .collect();
+    let call_ids: HashSet<&str> = events
+        .iter()
+        .filter(|event| event.kind == EventKind::ToolCall)
+        .filter_map(|event| event.message_id.as_deref())
+        .collect();
+
+    for event in events {
+        if event.is_delta == Some(true) {
+            continue;
+        }
+        match event.kind {
+            EventKind::User => render_message(event, "user", "User", &mut out),
+            EventKind::Assistant if event.role.as_deref() == Some("reasoning") => {
+                if options.include_reasoning {
+                    render_message(event, "reasoning", "Reasoning", &mut out);
+                }
+            }
+            EventKind::Assistant => render_message(event, "assistant", "Assistant", &mut out),
+            EventKind::ToolCall => {
+                let result = event
+                    .message_id
+                    .as_deref()
+                    .and_then(|id| results.get(id).copied());
+                render_tool_call(event, result, options, &mut out);
+            }
+            EventKind::ToolResult => {
+                // Results are shown with their call; only orphans are rendered here.
+                let has_call = event
+                    .parent_id
+                    .as_deref()
+                    .is_some_and(|id| call_ids.contains(id));
+                if !has_call {
+                    out.push_str("<section class=\"event tool\">\n<details>\n<summary>Tool output</summary>\n");
+                    render_output(event, options, &mut out);
+                    out.push_str("</details>\n</section>\n");
+                }
+            }
+            EventKind::Error => render_message(event, "error", "Error", &mut out),
+            EventKind::Meta => {}
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Writes the session as a single HTML file to `output_path`. Returns what was
+/// masked when `options.redact` is set.
+#[tauri::command]
+pub async fn export_session_html(
+    session_path: String,
+    output_path: String,
+    options: Option<ExportOptions>,
+) -> Result<Option<RedactionReport>, String> {
+    let options = options.unwrap_or_default();
+    let session = load_export_session(&session_path, &options)?;
+    let totals = load_price_table()
+        .and_then(|prices| read_turn_usage(&session_path, &prices))
+        .map(|(_, turns)| UsageTotals::from_turns(&turns))
+        .ok()
+        .filter(|totals| totals.usage.total_tokens > 0);
+    let html = render_html(&session.header, &session.events, totals.as_ref(), &options);
+    write_atomic(Path::new(&output_path), html.as_bytes())?;
+    Ok(session.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_files::events::parse_events;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    const HOSTILE: &str = r#"{"timestamp":"2025-09-20T10:01:00.000Z","type":"event_msg","payload":{"type":"user_message","message":"<script>alert('x')</script> & \"more\"","kind":"plain"}}
+{"timestamp":"2025-09-20T10:01:01.000Z","type":"response_item","payload":{"type":"function_call","name":"shell","call_id":"call_9","arguments":"{\"command\":[\"bash\",\"-lc\",\"false <&3\"]}"}}
+{"timestamp":"2025-09-20T10:01:02.000Z","type":"response_item","payload":{"type":"function_call_output","call_id":"call_9","output":"{\"output\":\"<b>boom</b>\",\"metadata\":{\"exit_code\":2}}"}}
+{"timestamp":"2025-09-20T10:01:03.000Z","type":"response_item","payload":{"type":"function_call_output","call_id":"call_orphan","output":"left over"}}
+"#;
+
+    fn render(rollout: &str, title: Option<&str>) -> String {
+        let events: Vec<SessionEvent> = parse_events(rollout.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let mut header = SessionHeader::from_events(&events);
+        header.title = title.map(str::to_string);
+        render_html(&header, &events, None, &ExportOptions::default())
+    }
+
+    #[test]
+    fn escape_covers_markup_and_quotes() {
+        assert_eq!(
+            escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn user_text_and_titles_cannot_inject_markup() {
+        let html = render(&format!("{}{}", ROLLOUT, HOSTILE), Some("</title><script>"));
+
+        assert!(!html.contains("<script>"), "{}", html);
+        assert!(!html.contains("<b>boom</b>"));
+        assert!(html.contains("<title>&lt;/title&gt;&lt;script&gt;</title>"));
+        assert!(html.contains("<h1>&lt;/title&gt;&lt;script&gt;</h1>"));
+        assert!(html.contains(
+            "<div class=\"message\">&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; &quot;more&quot;</div>"
+        ));
+        assert!(html.contains("<pre>$ false &lt;&amp;3</pre>"));
+    }
+
+    #[test]
+    fn tool_calls_are_grouped_with_their_results() {
+        let html = render(&format!("{}{}", ROLLOUT, HOSTILE), None);
+
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+        assert!(html.ends_with("</body>\n</html>\n"));
+        assert!(html.contains("<h1>Codex session 0199-abc</h1>"));
+        assert!(html.contains("<details>\n<summary>Instructions</summary>\n<pre>Be concise.</pre>"));
+
+        // The shell call and its output share one collapsed block.
+        assert!(html.contains(
+            "<details>\n<summary>cat db.rs (exit 0)</summary>\n<pre>$ cat db.rs</pre>\n<pre>fn migrate() {}</pre>\n</details>"
+        ));
+        // Failed commands are marked.
+        assert!(html
+            .contains("<details class=\"failed\">\n<summary>false &lt;&amp;3 (exit 2)</summary>"));
+        // Patches are highlighted as diffs.
+        assert!(html.contains("<summary>apply_patch (exit 0)</summary>\n<pre class=\"diff\"><span class=\"file\">*** Update File: db.rs</span>"));
+        assert!(!html.contains("*** Begin Patch"));
+        // Results without a call are still shown.
+        assert!(html.contains("<summary>Tool output</summary>\n<pre>left over</pre>"));
+    }
+
+    #[test]
+    fn diff_lines_get_classes() {
+        assert_eq!(
+            highlight_diff("*** Update File: a\n@@\n-old\n+new\n same"),
+            "<pre class=\"diff\"><span class=\"file\">*** Update File: a</span><span class=\"hunk\">@@</span><span class=\"del\">-old</span><span class=\"add\">+new</span><span class=\"context\"> same</span></pre>"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use super::events::{EventParser, SessionEvent};
+use super::utils::file_id;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// A follower stops once its rollout has been gone this long...
+const MISSING_TIMEOUT: Duration = Duration::from_secs(30);
+/// ...or has not grown this long, so one left behind by a closed window
+/// does not poll forever.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+static FOLLOWERS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static FOLLOW_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Sent over the `follow_session` channel.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FollowMessage {
+    Event {
+        event: Box<SessionEvent>,
+    },
+    /// The rollout was truncated or replaced; events start over from the
+    /// beginning and earlier ones should be discarded.
+    Reset,
+    /// The rollout has been idle or missing for too long and is no longer
+    /// followed; call `follow_session` again to resume.
+    Stopped,
+}
+
+/// Read position in a rollout that is still being written.
+struct Tail {
+    path: PathBuf,
+    /// Bytes parsed so far; always just after a newline.
+    offset: u64,
+    file_id: Option<u64>,
+    /// Event ids and tool names depend on earlier lines, so one parser
+    /// follows the whole file.
+    parser: EventParser,
+    /// File length at the last poll, and when it last changed.
+    seen_len: u64,
+    last_growth: Instant,
+    missing_since: Option<Instant>,
+}
+
+impl Tail {
+    fn new(path: PathBuf) -> Self {
+        Tail {
+            path,
+            offset: 0,
+            file_id: None,
+            parser: EventParser::new(),
+            seen_len: 0,
+            last_growth: Instant::now(),
+            missing_since: None,
+        }
+    }
+
+    /// Whether the rollout has been missing or unchanged for too long at `now`.
+    fn expired(&self, now: Instant) -> bool {
+        match self.missing_since {
+            Some(since) => now.duration_since(since) >= MISSING_TIMEOUT,
+            None => now.duration_since(self.last_growth) >= IDLE_TIMEOUT,
+        }
+    }
+
+    /// Events appended since the last poll. A last line without its newline
+    /// is left for the next poll, when Codex has finished writing it.
+    fn poll(&mut self) -> Result<Vec<FollowMessage>, String> {
+        let mut messages = Vec::new();
+        let Ok(metadata) = std::fs::metadata(&self.path) else {
+            // Possibly mid-replace; the next poll will tell.
+            self.missing_since.get_or_insert_with(Instant::now);
+            return Ok(messages);
+        };
+        self.missing_since = None;
+        if metadata.len() != self.seen_len {
+            self.seen_len = metadata.len();
+            self.last_growth = Instant::now();
+        }
+        let id = file_id(&metadata);
+        if metadata.len() < self.offset || (self.offset > 0 && id != self.file_id) {
+            self.offset = 0;
+            self.parser = EventParser::new();
+            messages.push(FollowMessage::Reset);
+        }
+        self.file_id = id;
+        if metadata.len() == self.offset {
+            return Ok(messages);
+        }
+
+        let mut file =
+            File::open(&self.path).map_err(|e| format!("Failed to open {:?}: {}", self.path, e))?;
+        let mut appended = Vec::new();
+        file.seek(SeekFrom::Start(self.offset))
+            .and_then(|_| file.read_to_end(&mut appended))
+            .map_err(|e| format!("Failed to read {:?}: {}", self.path, e))?;
+        let Some(end) = appended.iter().rposition(|b| *b == b'\n') else {
+            return Ok(messages);
+        };
+
+        for line in appended[..end].split(|b| *b == b'\n') {
+            let line = String::from_utf8_lossy(line);
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(event) = self.parser.parse_line(line) {
+                messages.push(FollowMessage::Event {
+                    event: Box::new(event),
+                });
+            }
+        }
+        self.offset += end as u64 + 1;
+        Ok(messages)
+    }
+}
+
+fn follow(mut tail: Tail, stop: &AtomicBool, on_event: &Channel<FollowMessage>) {
+    while !stop.load(Ordering::Relaxed) {
+        match tail.poll() {
+            Ok(messages) => {
+                for message in messages {
+                    // The window that asked for the events is gone.
+                    if on_event.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to follow {:?}: {}", tail.path, e),
+        }
+        if tail.expired(Instant::now()) {
+            let _ = on_event.send(FollowMessage::Stopped);
+            return;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Streams the events of a rollout over `on_event` like `tail -f`, until
+/// `unfollow_session` is called with the returned id or the file has been
+/// idle or missing for too long. With `skip_existing` only events appended
+/// from now on are sent.
+#[tauri::command]
+pub async fn follow_session(
+    session_path: String,
+    skip_existing: Option<bool>,
+    on_event: Channel<FollowMessage>,
+) -> Result<String, String> {
+    let path = PathBuf::from(&session_path);
+    if !path.is_file() {
+        return Err(format!("Session file not found: {}", session_path));
+    }
+
+    let mut tail = Tail::new(path);
+    if skip_existing.unwrap_or(false) {
+        tail.poll()?;
+    }
+
+    let follow_id = format!("follow-{}", FOLLOW_COUNTER.fetch_add(1, Ordering::Relaxed));
+    let stop = Arc::new(AtomicBool::new(false));
+    FOLLOWERS
+        .lock()
+        .map_err(|e| format!("Follower registry poisoned: {}", e))?
+        .insert(follow_id.clone(), stop.clone());
+
+    let thread_id = follow_id.clone();
+    std::thread::Builder::new()
+        .name(format!("session-{}", follow_id))
+        .spawn(move || {
+            follow(tail, &stop, &on_event);
+            if let Ok(mut followers) = FOLLOWERS.lock() {
+                followers.remove(&thread_id);
+            }
+        })
+        .map_err(|e| format!("Failed to follow {}: {}", session_path, e))?;
+    Ok(follow_id)
+}
+
+/// Stops a `follow_session` stream. Returns whether it was still running.
+#[tauri::command]
+pub async fn unfollow_session(follow_id: String) -> Result<bool, String> {
+    let followers = FOLLOWERS
+        .lock()
+        .map_err(|e| format!("Follower registry poisoned: {}", e))?;
+    Ok(followers
+        .get(&follow_id)
+        .map(|stop| stop.store(true, Ordering::Relaxed))
+        .is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    /// The `session_meta` line and the user's first message, one event each.
+    fn lines() -> Vec<&'static str> {
+        let lines: Vec<&str> = ROLLOUT.lines().collect();
+        vec![lines[0], lines[2]]
+    }
+
+    fn append(path: &std::path::Path, text: &str) {
+        let mut file = File::options()
+            .append(true)
+            .create(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn event_count(messages: &[FollowMessage]) -> usize {
+        messages
+            .iter()
+            .filter(|message| matches!(message, FollowMessage::Event { .. }))
+            .count()
+    }
+
+    #[test]
+    fn partial_lines_wait_for_their_newline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let lines = lines();
+        append(&path, &format!("{}\n", lines[0]));
+        let mut tail = Tail::new(path.clone());
+        assert_eq!(event_count(&tail.poll().unwrap()), 1);
+
+        let (head, rest) = lines[1].split_at(lines[1].len() / 2);
+        append(&path, head);
+        assert!(tail.poll().unwrap().is_empty());
+        append(&path, &format!("{}\n", rest));
+        let messages = tail.poll().unwrap();
+        assert_eq!(event_count(&messages), 1);
+        assert!(tail.poll().unwrap().is_empty());
+    }
+
+    #[test]
+    fn truncation_starts_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let lines = lines();
+        append(&path, &format!("{}\n{}\n", lines[0], lines[1]));
+        let mut tail = Tail::new(path.clone());
+        assert_eq!(event_count(&tail.poll().unwrap()), 2);
+
+        std::fs::write(&path, format!("{}\n", lines[0])).unwrap();
+        let messages = tail.poll().unwrap();
+        assert!(matches!(messages[0], FollowMessage::Reset));
+        assert_eq!(event_count(&messages), 1);
+    }
+
+    #[test]
+    fn replacement_starts_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let lines = lines();
+        append(&path, &format!("{}\n", lines[0]));
+        let mut tail = Tail::new(path.clone());
+        assert_eq!(event_count(&tail.poll().unwrap()), 1);
+
+        // A longer file swapped in under the same name.
+        let replacement = dir.path().join("replacement.jsonl");
+        append(&replacement, &format!("{}\n{}\n", lines[0], lines[1]));
+        std::fs::rename(&replacement, &path).unwrap();
+        let messages = tail.poll().unwrap();
+        assert!(matches!(messages[0], FollowMessage::Reset));
+        assert_eq!(event_count(&messages), 2);
+    }
+
+    #[test]
+    fn missing_and_idle_files_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        append(&path, &format!("{}\n", lines()[0]));
+        let mut tail = Tail::new(path.clone());
+        tail.poll().unwrap();
+        let now = Instant::now();
+        assert!(!tail.expired(now));
+        assert!(tail.expired(now + IDLE_TIMEOUT));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(tail.poll().unwrap().is_empty());
+        let now = Instant::now();
+        assert!(!tail.expired(now));
+        assert!(tail.expired(now + MISSING_TIMEOUT));
+    }
+}
@@ -1,9 +1,8 @@
-use base64::engine::general_purpose;
-use base64::engine::Engine as _;
 use std::path::PathBuf;
 
 use super::file::get_sessions_path;
 
+/// Directory for files that can always be rebuilt from the rollouts.
 pub fn get_cache_dir() -> Result<PathBuf, String> {
     let sessions_dir = get_sessions_path()?;
     let cache_dir = sessions_dir
@@ -15,7 +14,15 @@ pub fn get_cache_dir() -> Result<PathBuf, String> {
     Ok(cache_dir)
 }
 
-pub fn get_cache_path_for_project(project_path: &str) -> Result<PathBuf, String> {
-    let encoded = general_purpose::STANDARD.encode(project_path);
-    Ok(get_cache_dir()?.join(format!("{}.json", encoded)))
+/// Directory for data owned by codexsm itself (metadata, settings, trash, ...).
+/// Files derived from the rollouts, like the session catalog and the search
+/// index, go in `get_cache_dir` instead.
+pub fn get_app_data_dir() -> Result<PathBuf, String> {
+    let sessions_dir = get_sessions_path()?;
+    let data_dir = sessions_dir
+        .parent()
+        .ok_or("Could not get parent of sessions directory")?
+        .join("codexsm");
+    std::fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(data_dir)
 }
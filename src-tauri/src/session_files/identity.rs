@@ -0,0 +1,449 @@
+use super::cache::{sort_sessions, CachedSession};
+use super::catalog::{with_catalog, SessionCatalog};
+use super::get::get_app_data_dir;
+use super::metadata::{with_metadata, SessionEntry};
+use super::store::{with_file_lock, write_atomic};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+const ALIASES_FILE_NAME: &str = "project_aliases.json";
+
+/// Groups every project directory equal to, or inside, one of `paths` under `name`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectAlias {
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProjectIdentityConfig {
+    /// Group checkouts that share a remote URL, not just a repository.
+    pub group_by_git_remote: bool,
+    pub aliases: Vec<ProjectAlias>,
+}
+
+impl Default for ProjectIdentityConfig {
+    fn default() -> Self {
+        ProjectIdentityConfig {
+            group_by_git_remote: true,
+            aliases: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityKind {
+    Alias,
+    GitRemote,
+    GitRepository,
+    Path,
+}
+
+/// A project as the user thinks of it: one or more `cwd` values whose
+/// sessions are shown together.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalProject {
+    pub id: String,
+    pub name: String,
+    pub kind: IdentityKind,
+    pub paths: Vec<String>,
+}
+
+pub fn get_aliases_path() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join(ALIASES_FILE_NAME))
+}
+
+pub fn load_identity_config() -> Result<ProjectIdentityConfig, String> {
+    let aliases_path = get_aliases_path()?;
+    if !aliases_path.exists() {
+        return Ok(ProjectIdentityConfig::default());
+    }
+    let aliases_str = read_to_string(&aliases_path)
+        .map_err(|e| format!("Failed to read project aliases: {}", e))?;
+    serde_json::from_str(&aliases_str)
+        .map_err(|e| format!("Failed to parse project aliases: {}", e))
+}
+
+fn is_within(path: &str, root: &str) -> bool {
+    Path::new(path).starts_with(root)
+}
+
+/// `host/owner/repo` for the usual URL spellings, so `git@github.com:me/app.git`,
+/// `ssh://git@github.com:22/me/app` and `https://GitHub.com/me/app` compare
+/// equal. Only the host is lowercased; many servers treat paths case-sensitively.
+pub fn normalize_remote_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/').trim_end_matches(".git");
+    let (has_scheme, without_scheme) = match url.split_once("://") {
+        Some((_, rest)) => (true, rest),
+        None => (false, url),
+    };
+    let without_user = without_scheme
+        .split_once('@')
+        .map_or(without_scheme, |(_, rest)| rest);
+    let (host, path) = if has_scheme {
+        // `host[:port]/owner/repo`; the port does not identify the repository.
+        let (authority, path) = without_user.split_once('/').unwrap_or((without_user, ""));
+        let host = authority
+            .split_once(':')
+            .map_or(authority, |(host, _)| host);
+        (host, path)
+    } else {
+        // scp-like syntax: `host:owner/repo`
+        match without_user.split_once(':') {
+            Some((host, rest)) if !rest.starts_with('/') && !host.contains('/') => (host, rest),
+            // A local path, used as it is.
+            _ => return without_user.to_string(),
+        }
+    };
+    format!("{}/{}", host.to_lowercase(), path)
+}
+
+/// Remote URLs in a git config file, `origin` first.
+fn remote_urls(config: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut remote: Option<String> = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            remote = line
+                .strip_prefix("[remote \"")
+                .and_then(|rest| rest.strip_suffix("\"]"))
+                .map(str::to_string);
+        } else if let (Some(name), Some((key, value))) = (&remote, line.split_once('=')) {
+            if key.trim() == "url" {
+                urls.push((name != "origin", value.trim().to_string()));
+            }
+        }
+    }
+    urls.sort_by_key(|(not_origin, _)| *not_origin);
+    urls.into_iter().map(|(_, url)| url).collect()
+}
+
+/// The shared `.git` directory of the repository containing `path`. For a
+/// worktree this is the main repository's, read through `gitdir` and `commondir`.
+fn git_common_dir(path: &Path) -> Option<PathBuf> {
+    for dir in path.ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if dot_git.is_file() {
+            let contents = read_to_string(&dot_git).ok()?;
+            let git_dir = contents.trim().strip_prefix("gitdir:")?.trim();
+            let git_dir = dir.join(git_dir);
+            return Some(match read_to_string(git_dir.join("commondir")) {
+                Ok(common) => git_dir.join(common.trim()),
+                Err(_) => git_dir,
+            });
+        }
+    }
+    None
+}
+
+/// Identity of a project directory that is not covered by an alias.
+fn git_identity(
+    project_path: &str,
+    recorded_remote: Option<&str>,
+    group_by_remote: bool,
+) -> Option<(IdentityKind, String, String)> {
+    let common_dir = git_common_dir(Path::new(project_path));
+    let local_remote = common_dir
+        .as_ref()
+        .and_then(|dir| read_to_string(dir.join("config")).ok())
+        .and_then(|config| remote_urls(&config).into_iter().next());
+
+    if group_by_remote {
+        // The checkout may be gone; the session still records its remote.
+        if let Some(remote) = local_remote.as_deref().or(recorded_remote) {
+            let remote = normalize_remote_url(remote);
+            let name = remote.rsplit('/').next().unwrap_or(&remote).to_string();
+            return Some((IdentityKind::GitRemote, remote, name));
+        }
+    }
+
+    let common_dir = std::fs::canonicalize(common_dir?).ok()?;
+    let root = common_dir.parent()?;
+    let name = root.file_name()?.to_string_lossy().to_string();
+    Some((
+        IdentityKind::GitRepository,
+        root.to_string_lossy().to_string(),
+        name,
+    ))
+}
+
+/// Every project directory with rollouts, with the remote URL its sessions recorded.
+fn project_remotes(catalog: &SessionCatalog) -> BTreeMap<String, Option<String>> {
+    let mut projects: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for entry in catalog.entries.values() {
+        let Some(cwd) = &entry.cwd else {
+            continue;
+        };
+        let recorded = projects.entry(cwd.clone()).or_default();
+        if recorded.is_none() {
+            recorded.clone_from(&entry.git_remote);
+        }
+    }
+    projects
+}
+
+fn alias_for<'a>(
+    config: &'a ProjectIdentityConfig,
+    project_path: &str,
+) -> Option<&'a ProjectAlias> {
+    config
+        .aliases
+        .iter()
+        .find(|alias| alias.paths.iter().any(|p| is_within(project_path, p)))
+}
+
+/// Kind, key and display name of the logical project a directory belongs to:
+/// its alias, else its git identity, else the directory itself.
+fn identify(
+    config: &ProjectIdentityConfig,
+    project_path: &str,
+    recorded_remote: Option<&str>,
+) -> (IdentityKind, String, String) {
+    if let Some(alias) = alias_for(config, project_path) {
+        return (IdentityKind::Alias, alias.name.clone(), alias.name.clone());
+    }
+    git_identity(project_path, recorded_remote, config.group_by_git_remote).unwrap_or_else(|| {
+        let name = Path::new(project_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| project_path.to_string());
+        (IdentityKind::Path, project_path.to_string(), name)
+    })
+}
+
+fn project_id(kind: IdentityKind, key: &str) -> String {
+    let prefix = match kind {
+        IdentityKind::Alias => "alias",
+        IdentityKind::GitRemote => "remote",
+        IdentityKind::GitRepository => "git",
+        IdentityKind::Path => "path",
+    };
+    format!("{}:{}", prefix, key)
+}
+
+/// Groups project directories, as returned by `project_remotes`, into
+/// logical projects.
+fn group_projects(
+    config: &ProjectIdentityConfig,
+    remotes: BTreeMap<String, Option<String>>,
+) -> Vec<LogicalProject> {
+    let mut groups: HashMap<String, LogicalProject> = HashMap::new();
+    for (project_path, recorded_remote) in remotes {
+        let (kind, key, name) = identify(config, &project_path, recorded_remote.as_deref());
+        let id = project_id(kind, &key);
+        groups
+            .entry(id.clone())
+            .or_insert_with(|| LogicalProject {
+                id,
+                name,
+                kind,
+                paths: Vec::new(),
+            })
+            .paths
+            .push(project_path);
+    }
+
+    let mut projects: Vec<LogicalProject> = groups.into_values().collect();
+    projects.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+    projects
+}
+
+/// The logical project with id `id`. Directories that cannot belong to it
+/// judging by the id alone are not looked at, so resolving an alias or a
+/// plain path never reads git configuration.
+fn find_project(
+    config: &ProjectIdentityConfig,
+    remotes: BTreeMap<String, Option<String>>,
+    id: &str,
+) -> Option<LogicalProject> {
+    let mut project: Option<LogicalProject> = None;
+    for (project_path, recorded_remote) in remotes {
+        let possible = match id.split_once(':') {
+            Some(("alias", name)) => {
+                alias_for(config, &project_path).is_some_and(|alias| alias.name == name)
+            }
+            Some(("path", path)) => project_path == path,
+            _ => true,
+        };
+        if !possible {
+            continue;
+        }
+        let (kind, key, name) = identify(config, &project_path, recorded_remote.as_deref());
+        if project_id(kind, &key) != id {
+            continue;
+        }
+        project
+            .get_or_insert_with(|| LogicalProject {
+                id: id.to_string(),
+                name,
+                kind,
+                paths: Vec::new(),
+            })
+            .paths
+            .push(project_path);
+    }
+    project
+}
+
+pub fn resolve_projects(config: &ProjectIdentityConfig) -> Result<Vec<LogicalProject>, String> {
+    Ok(group_projects(config, with_catalog(project_remotes)?))
+}
+
+#[tauri::command]
+pub async fn get_project_aliases() -> Result<ProjectIdentityConfig, String> {
+    load_identity_config()
+}
+
+#[tauri::command]
+pub async fn set_project_aliases(config: ProjectIdentityConfig) -> Result<(), String> {
+    let aliases_path = get_aliases_path()?;
+    let json_str = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize project aliases: {}", e))?;
+    with_file_lock(&aliases_path, || {
+        write_atomic(&aliases_path, json_str.as_bytes())
+    })
+}
+
+#[tauri::command]
+pub async fn get_logical_projects() -> Result<Vec<LogicalProject>, String> {
+    resolve_projects(&load_identity_config()?)
+}
+
+/// Sessions of every checkout that belongs to a logical project, newest first.
+#[tauri::command]
+pub async fn get_logical_project_sessions(project_id: String) -> Result<Value, String> {
+    let config = load_identity_config()?;
+    let project = find_project(&config, with_catalog(project_remotes)?, &project_id)
+        .ok_or_else(|| format!("Unknown project {}", project_id))?;
+
+    let mut sessions: Vec<CachedSession> = with_catalog(|catalog| {
+        catalog
+            .entries
+            .values()
+            .filter(|entry| {
+                entry
+                    .cwd
+                    .as_ref()
+                    .is_some_and(|cwd| project.paths.contains(cwd))
+            })
+            .filter_map(|entry| entry.session.clone())
+            .collect()
+    })?;
+    sort_sessions(&mut sessions);
+    let entries: Vec<SessionEntry> = with_metadata(sessions)?;
+    Ok(serde_json::json!({ "project": project, "sessions": entries }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_url_spellings_normalize_alike() {
+        for url in [
+            "git@GitHub.com:me/app.git",
+            "https://github.com/me/app",
+            "https://user@github.com/me/app.git/",
+            "ssh://git@github.com/me/app.git",
+            "ssh://git@github.com:22/me/app",
+        ] {
+            assert_eq!(normalize_remote_url(url), "github.com/me/app", "{}", url);
+        }
+    }
+
+    #[test]
+    fn remote_url_paths_keep_their_case() {
+        assert_eq!(
+            normalize_remote_url("git@GITHUB.COM:Me/App.git"),
+            "github.com/Me/App"
+        );
+        assert_eq!(
+            normalize_remote_url("https://Git.Example.com/Team/Repo"),
+            "git.example.com/Team/Repo"
+        );
+        assert_eq!(normalize_remote_url("/srv/Git/App.git"), "/srv/Git/App");
+    }
+
+    /// A checkout at `dir/name` whose `origin` is `remote`.
+    fn checkout(dir: &Path, name: &str, remote: &str) -> String {
+        let git_dir = dir.join(name).join(".git");
+        std::fs::create_dir_all(&git_dir).unwrap();
+        let config = format!(
+            "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = {}\n",
+            remote
+        );
+        std::fs::write(git_dir.join("config"), config).unwrap();
+        dir.join(name).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn projects_are_grouped_by_alias_remote_and_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = checkout(dir.path(), "app", "git@github.com:me/app.git");
+        let app_copy = checkout(dir.path(), "app-copy", "https://github.com/me/app");
+        let plain = dir.path().join("notes").to_string_lossy().to_string();
+        let scratch = dir.path().join("scratch").to_string_lossy().to_string();
+        let config = ProjectIdentityConfig {
+            group_by_git_remote: true,
+            aliases: vec![ProjectAlias {
+                name: "Playground".to_string(),
+                paths: vec![scratch.clone()],
+            }],
+        };
+        let remotes: BTreeMap<String, Option<String>> = [
+            (app.clone(), None),
+            (app_copy.clone(), None),
+            (plain.clone(), None),
+            (format!("{}/one", scratch), None),
+            (format!("{}/two", scratch), None),
+            // Gone from disk, but the session recorded its remote.
+            (
+                "/nowhere/app".to_string(),
+                Some("git@github.com:me/app.git".to_string()),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let projects = group_projects(&config, remotes.clone());
+        let summary: Vec<(&str, usize)> = projects
+            .iter()
+            .map(|project| (project.id.as_str(), project.paths.len()))
+            .collect();
+        let plain_id = format!("path:{}", plain);
+        assert_eq!(
+            summary,
+            vec![
+                ("alias:Playground", 2),
+                ("remote:github.com/me/app", 3),
+                (plain_id.as_str(), 1),
+            ]
+        );
+
+        for project in &projects {
+            let found = find_project(&config, remotes.clone(), &project.id).unwrap();
+            assert_eq!(found.name, project.name);
+            assert_eq!(found.kind, project.kind);
+            assert_eq!(found.paths, project.paths);
+        }
+        assert!(find_project(&config, remotes.clone(), "alias:Unknown").is_none());
+        assert!(find_project(&config, remotes, "path:/nowhere/app").is_none());
+    }
+
+    #[test]
+    fn aliases_match_whole_path_components() {
+        assert!(is_within("/home/me/app", "/home/me/app"));
+        assert!(is_within("/home/me/app/web", "/home/me/app/"));
+        assert!(!is_within("/home/me/application", "/home/me/app"));
+    }
+}
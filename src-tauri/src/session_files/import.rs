@@ -0,0 +1,655 @@
+use super::catalog::with_catalog;
+use super::file::{get_sessions_path, read_session_cwd};
+use super::quarantine::load_cleanup_settings;
+use super::save::sync_session_files;
+use super::store::temp_path_for;
+use super::trash::{get_trash_dir, restore_in, trash_files_in};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Leave the existing session alone and report the conflict.
+    #[default]
+    Skip,
+    /// Move the existing session to the trash and import the new one.
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Imported,
+    Replaced,
+    Conflict,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOutcome {
+    /// File name inside the source (or the source itself for a single file).
+    pub source: String,
+    pub status: ImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    /// Where the session lives now: the imported file, or the existing one on conflict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ImportOutcome {
+    fn invalid(source: String, message: String) -> Self {
+        ImportOutcome {
+            source,
+            status: ImportStatus::Invalid,
+            conversation_id: None,
+            path: None,
+            message: Some(message),
+        }
+    }
+}
+
+fn is_rollout_name(name: &str) -> bool {
+    name.ends_with(".jsonl") && !name.split('/').any(|part| part.starts_with('.'))
+}
+
+/// Hands every rollout in a zip archive to `on_file`, one entry at a time.
+fn read_zip(
+    source_path: &Path,
+    on_file: &mut impl FnMut(String, &mut dyn Read),
+) -> Result<(), String> {
+    let file = File::open(source_path)
+        .map_err(|e| format!("Failed to open archive {:?}: {}", source_path, e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+        if !entry.is_file() || !is_rollout_name(entry.name()) {
+            continue;
+        }
+        on_file(entry.name().to_string(), &mut entry);
+    }
+    Ok(())
+}
+
+/// Hands every rollout in a tar stream to `on_file`, one entry at a time.
+fn read_tar<R: Read>(
+    reader: R,
+    on_file: &mut impl FnMut(String, &mut dyn Read),
+) -> Result<(), String> {
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read tar archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read tar entry: {}", e))?;
+        let name = entry
+            .path()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !entry.header().entry_type().is_file() || !is_rollout_name(&name) {
+            continue;
+        }
+        on_file(name, &mut entry);
+    }
+    Ok(())
+}
+
+/// Streams the rollouts of the import source to `on_file` without holding
+/// more than one of them open at a time.
+fn read_source(
+    source_path: &Path,
+    mut on_file: impl FnMut(String, &mut dyn Read),
+) -> Result<(), String> {
+    let file_name = source_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let name = file_name.to_lowercase();
+    let open =
+        || File::open(source_path).map_err(|e| format!("Failed to open {:?}: {}", source_path, e));
+
+    if name.ends_with(".zip") {
+        read_zip(source_path, &mut on_file)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        read_tar(flate2::read::GzDecoder::new(open()?), &mut on_file)
+    } else if name.ends_with(".tar") {
+        read_tar(open()?, &mut on_file)
+    } else if name.ends_with(".jsonl") {
+        on_file(file_name, &mut open()?);
+        Ok(())
+    } else {
+        Err(format!(
+            "Unsupported import file {:?}: expected .jsonl, .zip, .tar or .tar.gz",
+            source_path
+        ))
+    }
+}
+
+/// The first line of a rollout, if it is a `session_meta` record with an id.
+fn session_meta(first_line: &[u8]) -> Option<Value> {
+    let value: Value = serde_json::from_slice(first_line).ok()?;
+    if value["type"] != "session_meta" || !value["payload"]["id"].is_string() {
+        return None;
+    }
+    Some(value)
+}
+
+/// Path under the sessions directory for a rollout, laid out like Codex does
+/// (`YYYY/MM/DD/rollout-<timestamp>-<id>.jsonl`) so `extract_datetime` works.
+fn target_path(
+    sessions_dir: &Path,
+    source: &str,
+    conversation_id: &str,
+    meta: &Value,
+) -> Option<PathBuf> {
+    let file_name = Path::new(source).file_name()?.to_string_lossy().to_string();
+    // A Codex file name already carries the timestamp; reuse it as is.
+    let from_name = file_name
+        .strip_prefix("rollout-")
+        .and_then(|rest| rest.get(..19))
+        .and_then(|ts| NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H-%M-%S").ok());
+    let (datetime, file_name) = match from_name {
+        Some(datetime) => (datetime, file_name),
+        None => {
+            // The id ends up in the file name, so keep it to plain characters.
+            let safe_id = conversation_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !safe_id {
+                return None;
+            }
+            let timestamp = meta["payload"]["timestamp"]
+                .as_str()
+                .or_else(|| meta["timestamp"].as_str())?;
+            // Codex names day directories and files in local time.
+            let datetime = DateTime::parse_from_rfc3339(timestamp)
+                .ok()?
+                .with_timezone(&Local)
+                .naive_local();
+            let file_name = format!(
+                "rollout-{}-{}.jsonl",
+                datetime.format("%Y-%m-%dT%H-%M-%S"),
+                conversation_id
+            );
+            (datetime, file_name)
+        }
+    };
+
+    // The name comes from the import (or its session id), so it must be a
+    // single plain component that cannot leave the day directory.
+    let mut components = Path::new(&file_name).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return None;
+    }
+    Some(
+        sessions_dir
+            .join(datetime.format("%Y").to_string())
+            .join(datetime.format("%m").to_string())
+            .join(datetime.format("%d").to_string())
+            .join(file_name),
+    )
+}
+
+/// Path of every session already under the sessions directory, by
+/// conversation id.
+fn existing_sessions() -> Result<HashMap<String, PathBuf>, String> {
+    with_catalog(|catalog| {
+        catalog
+            .entries
+            .iter()
+            .filter_map(|(path, entry)| {
+                let session = entry.session.as_ref()?;
+                Some((session.conversation_id.clone(), PathBuf::from(path)))
+            })
+            .collect()
+    })
+}
+
+/// A rollout copied to a temporary file next to its target. The sessions
+/// directory is only touched once the copy is complete; an abandoned copy is
+/// removed.
+struct StagedFile {
+    temp_path: PathBuf,
+}
+
+impl StagedFile {
+    fn write(target: &Path, first_line: &[u8], rest: &mut dyn Read) -> Result<Self, String> {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let staged = StagedFile {
+            temp_path: temp_path_for(target),
+        };
+        File::create(&staged.temp_path)
+            .and_then(|mut file| {
+                file.write_all(first_line)?;
+                std::io::copy(rest, &mut file)?;
+                file.sync_all()
+            })
+            .map_err(|e| format!("Failed to write {:?}: {}", target, e))?;
+        Ok(staged)
+    }
+
+    fn commit(self, target: &Path) -> Result<(), String> {
+        std::fs::rename(&self.temp_path, target)
+            .map_err(|e| format!("Failed to write {:?}: {}", target, e))
+    }
+}
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.temp_path);
+    }
+}
+
+struct Importer {
+    sessions_dir: PathBuf,
+    trash_dir: PathBuf,
+    on_conflict: ConflictPolicy,
+    /// Existing sessions modified more recently than this may still be
+    /// written by Codex and are never replaced.
+    active_grace: Duration,
+    existing: HashMap<String, PathBuf>,
+    imported_ids: HashSet<String>,
+    /// Imported and replaced rollouts, for the session catalog.
+    changed: Vec<PathBuf>,
+    outcomes: Vec<ImportOutcome>,
+}
+
+impl Importer {
+    fn new(
+        sessions_dir: PathBuf,
+        trash_dir: PathBuf,
+        on_conflict: ConflictPolicy,
+        active_grace: Duration,
+        existing: HashMap<String, PathBuf>,
+    ) -> Self {
+        Importer {
+            sessions_dir,
+            trash_dir,
+            on_conflict,
+            active_grace,
+            existing,
+            imported_ids: HashSet::new(),
+            changed: Vec::new(),
+            outcomes: Vec::new(),
+        }
+    }
+
+    fn import(&mut self, source: String, reader: &mut dyn Read) {
+        let outcome = self
+            .import_file(&source, reader)
+            .unwrap_or_else(|message| ImportOutcome::invalid(source, message));
+        self.outcomes.push(outcome);
+    }
+
+    fn import_file(
+        &mut self,
+        source: &str,
+        reader: &mut dyn Read,
+    ) -> Result<ImportOutcome, String> {
+        let mut reader = BufReader::new(reader);
+        let mut first_line = Vec::new();
+        reader
+            .read_until(b'\n', &mut first_line)
+            .map_err(|e| format!("Failed to read {}: {}", source, e))?;
+        let meta = session_meta(&first_line)
+            .ok_or("First line is not a session_meta record with an id")?;
+        let conversation_id = meta["payload"]["id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let target = target_path(&self.sessions_dir, source, &conversation_id, &meta)
+            .ok_or("Could not determine the session date or file name")?;
+        if !self.imported_ids.insert(conversation_id.clone()) {
+            return Err(format!(
+                "Session {} appears more than once in the import",
+                conversation_id
+            ));
+        }
+
+        let conflict = |path: &Path, message: &str| ImportOutcome {
+            source: source.to_string(),
+            status: ImportStatus::Conflict,
+            conversation_id: Some(conversation_id.clone()),
+            path: Some(path.to_string_lossy().to_string()),
+            message: Some(message.to_string()),
+        };
+        let existing_path = self.existing.get(&conversation_id).cloned();
+        if target.exists() && existing_path.as_ref() != Some(&target) {
+            return Ok(conflict(
+                &target,
+                "A different session already uses this file name",
+            ));
+        }
+        if let Some(existing_path) = &existing_path {
+            if self.on_conflict == ConflictPolicy::Skip {
+                return Ok(conflict(
+                    existing_path,
+                    "A session with this id already exists",
+                ));
+            }
+            let modified: Option<DateTime<Utc>> = std::fs::metadata(existing_path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(Into::into);
+            if modified.is_none_or(|modified| modified > Utc::now() - self.active_grace) {
+                return Ok(conflict(
+                    existing_path,
+                    "The existing session may still be in use",
+                ));
+            }
+        }
+
+        // Copy the new rollout in full before the existing one is touched.
+        let staged = StagedFile::write(&target, &first_line, &mut reader)?;
+        let mut status = ImportStatus::Imported;
+        let mut trashed_id = None;
+        if let Some(existing_path) = &existing_path {
+            let existing_str = existing_path.to_string_lossy().to_string();
+            let project_path = read_session_cwd(existing_path).unwrap_or_default();
+            let sessions_dir = std::fs::canonicalize(&self.sessions_dir)
+                .map_err(|e| format!("Failed to resolve sessions dir: {}", e))?;
+            let outcome = trash_files_in(
+                &self.trash_dir,
+                &sessions_dir,
+                &project_path,
+                std::slice::from_ref(&existing_str),
+            )?
+            .pop()
+            .ok_or("Failed to replace session: no outcome")?;
+            match (outcome.entry, outcome.error) {
+                (Some(entry), _) => trashed_id = Some(entry.id),
+                (None, error) => {
+                    return Err(error.unwrap_or_else(|| "Failed to replace session".to_string()))
+                }
+            }
+            self.existing.remove(&conversation_id);
+            self.changed.push(existing_path.clone());
+            status = ImportStatus::Replaced;
+        }
+        if let Err(e) = staged.commit(&target) {
+            // Put the replaced session back rather than lose both.
+            if let Some(id) = trashed_id {
+                if let Err(restore_error) = restore_in(&self.trash_dir, &[id]) {
+                    eprintln!("Failed to restore replaced session: {}", restore_error);
+                }
+            }
+            return Err(e);
+        }
+
+        self.changed.push(target.clone());
+        self.existing
+            .insert(conversation_id.clone(), target.clone());
+        Ok(ImportOutcome {
+            source: source.to_string(),
+            status,
+            conversation_id: Some(conversation_id),
+            path: Some(target.to_string_lossy().to_string()),
+            message: None,
+        })
+    }
+}
+
+/// Imports a `.jsonl` rollout or a zip/tar archive of rollouts into the
+/// sessions directory and adds them to the session catalog. Each file gets
+/// its own outcome; one bad file does not stop the rest.
+#[tauri::command]
+pub async fn import_sessions(
+    source_path: String,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<Vec<ImportOutcome>, String> {
+    let settings = load_cleanup_settings()?;
+    let mut importer = Importer::new(
+        get_sessions_path()?,
+        get_trash_dir()?,
+        on_conflict.unwrap_or_default(),
+        Duration::minutes(settings.active_grace_minutes),
+        existing_sessions()?,
+    );
+    read_source(Path::new(&source_path), |source, reader| {
+        importer.import(source, reader)
+    })?;
+
+    if let Err(e) = sync_session_files(&importer.changed) {
+        eprintln!("Failed to update session catalog: {}", e);
+    }
+
+    Ok(importer.outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+    const CODEX_NAME: &str = "rollout-2025-09-20T10-00-00-0199-abc.jsonl";
+
+    struct Dirs {
+        root: tempfile::TempDir,
+        sessions: PathBuf,
+        trash: PathBuf,
+    }
+
+    fn dirs() -> Dirs {
+        let root = tempfile::tempdir().unwrap();
+        let sessions = root.path().join("sessions");
+        let trash = root.path().join("trash");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::create_dir_all(&trash).unwrap();
+        Dirs {
+            root,
+            sessions,
+            trash,
+        }
+    }
+
+    fn import(
+        dirs: &Dirs,
+        source: &Path,
+        on_conflict: ConflictPolicy,
+        existing: HashMap<String, PathBuf>,
+    ) -> Vec<ImportOutcome> {
+        let mut importer = Importer::new(
+            dirs.sessions.clone(),
+            dirs.trash.clone(),
+            on_conflict,
+            Duration::minutes(60),
+            existing,
+        );
+        read_source(source, |name, reader| importer.import(name, reader)).unwrap();
+        importer.outcomes
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// Writes names as given, including ones `tar::Builder` would refuse.
+    fn write_tar(path: &Path, files: &[(&str, &str)]) {
+        let mut tar = tar::Builder::new(File::create(path).unwrap());
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append(&header, contents.as_bytes()).unwrap();
+        }
+        tar.finish().unwrap();
+    }
+
+    /// Every file under `dir`, relative to it.
+    fn files_under(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| {
+                e.path()
+                    .strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn assert_stays_inside(dirs: &Dirs, outcomes: &[ImportOutcome], archive: &str) {
+        assert_eq!(outcomes.len(), 1, "{:?}", outcomes);
+        assert_eq!(outcomes[0].status, ImportStatus::Imported);
+        assert_eq!(
+            files_under(dirs.root.path()),
+            vec![
+                archive.to_string(),
+                format!("sessions/2025/09/20/{}", CODEX_NAME)
+            ]
+        );
+    }
+
+    #[test]
+    fn zip_names_cannot_leave_the_sessions_dir() {
+        let dirs = dirs();
+        let archive = dirs.root.path().join("in.zip");
+        let absolute = format!("/tmp/elsewhere/{}", CODEX_NAME);
+        write_zip(
+            &archive,
+            &[("../../evil.jsonl", ROLLOUT), (absolute.as_str(), ROLLOUT)],
+        );
+
+        let outcomes = import(&dirs, &archive, ConflictPolicy::Skip, HashMap::new());
+        assert_stays_inside(&dirs, &outcomes, "in.zip");
+    }
+
+    #[test]
+    fn tar_names_cannot_leave_the_sessions_dir() {
+        let dirs = dirs();
+        let archive = dirs.root.path().join("in.tar");
+        let absolute = format!("/tmp/elsewhere/{}", CODEX_NAME);
+        write_tar(
+            &archive,
+            &[("../../evil.jsonl", ROLLOUT), (absolute.as_str(), ROLLOUT)],
+        );
+
+        let outcomes = import(&dirs, &archive, ConflictPolicy::Skip, HashMap::new());
+        assert_stays_inside(&dirs, &outcomes, "in.tar");
+    }
+
+    #[test]
+    fn rollouts_without_a_session_meta_id_are_invalid() {
+        let dirs = dirs();
+        let lines: Vec<&str> = ROLLOUT.lines().collect();
+        let no_meta = lines[1..].join("\n");
+        let no_id = ROLLOUT.replacen(r#""id":"0199-abc","#, "", 1);
+        assert_ne!(no_id, ROLLOUT);
+        let archive = dirs.root.path().join("in.zip");
+        write_zip(
+            &archive,
+            &[("no-meta.jsonl", &no_meta), ("no-id.jsonl", &no_id)],
+        );
+
+        let outcomes = import(&dirs, &archive, ConflictPolicy::Skip, HashMap::new());
+        assert_eq!(outcomes.len(), 2);
+        for outcome in &outcomes {
+            assert_eq!(outcome.status, ImportStatus::Invalid, "{:?}", outcome);
+            assert!(outcome.message.as_ref().unwrap().contains("session_meta"));
+        }
+        assert!(files_under(&dirs.sessions).is_empty());
+    }
+
+    #[test]
+    fn files_without_a_codex_name_are_placed_by_local_date() {
+        let dirs = dirs();
+        let source = dirs.root.path().join("exported.jsonl");
+        std::fs::write(&source, ROLLOUT).unwrap();
+
+        let outcomes = import(&dirs, &source, ConflictPolicy::Skip, HashMap::new());
+        let local = DateTime::parse_from_rfc3339("2025-09-20T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Local);
+        let expected = dirs
+            .sessions
+            .join(local.format("%Y/%m/%d").to_string())
+            .join(format!(
+                "rollout-{}-0199-abc.jsonl",
+                local.format("%Y-%m-%dT%H-%M-%S")
+            ));
+        assert_eq!(outcomes[0].status, ImportStatus::Imported);
+        assert_eq!(
+            outcomes[0].path.as_deref(),
+            Some(&*expected.to_string_lossy())
+        );
+        assert_eq!(std::fs::read_to_string(&expected).unwrap(), ROLLOUT);
+    }
+
+    #[test]
+    fn skip_keeps_and_replace_trashes_the_existing_session() {
+        let dirs = dirs();
+        let existing_path = dirs.sessions.join("2025/09/20").join(CODEX_NAME);
+        std::fs::create_dir_all(existing_path.parent().unwrap()).unwrap();
+        let existing = || HashMap::from([("0199-abc".to_string(), existing_path.clone())]);
+        let set_age = |age: u64| {
+            File::options()
+                .write(true)
+                .open(&existing_path)
+                .unwrap()
+                .set_modified(SystemTime::now() - std::time::Duration::from_secs(age))
+                .unwrap();
+        };
+        std::fs::write(&existing_path, "old").unwrap();
+        let source = dirs.root.path().join(CODEX_NAME);
+        std::fs::write(&source, ROLLOUT).unwrap();
+
+        set_age(2 * 3600);
+        let outcomes = import(&dirs, &source, ConflictPolicy::Skip, existing());
+        assert_eq!(outcomes[0].status, ImportStatus::Conflict);
+        assert_eq!(std::fs::read_to_string(&existing_path).unwrap(), "old");
+
+        // A session Codex may still be writing is never replaced.
+        set_age(60);
+        let outcomes = import(&dirs, &source, ConflictPolicy::Replace, existing());
+        assert_eq!(outcomes[0].status, ImportStatus::Conflict);
+        assert!(outcomes[0].message.as_ref().unwrap().contains("in use"));
+        assert_eq!(std::fs::read_to_string(&existing_path).unwrap(), "old");
+
+        set_age(2 * 3600);
+        let outcomes = import(&dirs, &source, ConflictPolicy::Replace, existing());
+        assert_eq!(outcomes[0].status, ImportStatus::Replaced);
+        assert_eq!(std::fs::read_to_string(&existing_path).unwrap(), ROLLOUT);
+        let trashed: Vec<String> = files_under(&dirs.trash)
+            .into_iter()
+            .filter(|name| name.ends_with(CODEX_NAME))
+            .collect();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dirs.trash.join(&trashed[0])).unwrap(),
+            "old"
+        );
+    }
+}
@@ -0,0 +1,607 @@
+use super::catalog::with_catalog;
+use super::events::open_session_events;
+use super::get::get_cache_dir;
+use super::search::{build_matcher, make_snippet, searchable_text, SearchHit, SearchMode};
+use super::store::write_atomic;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const INDEX_VERSION: u32 = 2;
+const INDEX_DIR_NAME: &str = "search_index";
+const DEFAULT_RANKED_LIMIT: usize = 50;
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+static SEARCH_INDEX: Mutex<Option<SearchIndex>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Posting {
+    file: u32,
+    event: u32,
+    tf: u32,
+    /// Token count of the event, for BM25 length normalization.
+    len: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedFile {
+    path: String,
+    conversation_id: String,
+    cwd: Option<String>,
+    /// Size and mtime the session catalog had for the file when it was indexed.
+    size_bytes: u64,
+    modified_at: Option<DateTime<Utc>>,
+    docs: u64,
+    length: u64,
+    /// Distinct terms of the file, used to drop its postings on removal.
+    #[serde(skip)]
+    terms: Vec<String>,
+}
+
+/// One rollout's share of the index, stored as `<id>.json` so that indexing
+/// or dropping a file only writes or deletes its own segment.
+#[derive(Debug, Serialize, Deserialize)]
+struct Segment {
+    version: u32,
+    id: u32,
+    file: IndexedFile,
+    postings: Vec<(String, Posting)>,
+}
+
+/// Inverted index over the searchable text of every session event, kept in
+/// memory and stored as one segment per rollout in `scan_cache/search_index`.
+#[derive(Debug)]
+pub struct SearchIndex {
+    dir: PathBuf,
+    next_file_id: u32,
+    total_docs: u64,
+    total_length: u64,
+    files: HashMap<u32, IndexedFile>,
+    postings: HashMap<String, Vec<Posting>>,
+    ids_by_path: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedSearchHit {
+    #[serde(flatten)]
+    pub hit: SearchHit,
+    pub score: f64,
+}
+
+/// A catalogued session, as the index records it.
+struct CatalogFile {
+    path: String,
+    cwd: Option<String>,
+    size_bytes: u64,
+    modified_at: Option<DateTime<Utc>>,
+}
+
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.chars().count() >= 2 && token.len() <= 64)
+        .map(str::to_lowercase)
+}
+
+fn get_index_dir() -> Result<PathBuf, String> {
+    let index_dir = get_cache_dir()?.join(INDEX_DIR_NAME);
+    std::fs::create_dir_all(&index_dir)
+        .map_err(|e| format!("Failed to create search index dir: {}", e))?;
+    Ok(index_dir)
+}
+
+impl SearchIndex {
+    fn new(dir: PathBuf) -> Self {
+        SearchIndex {
+            dir,
+            next_file_id: 0,
+            total_docs: 0,
+            total_length: 0,
+            files: HashMap::new(),
+            postings: HashMap::new(),
+            ids_by_path: HashMap::new(),
+        }
+    }
+
+    /// Loads every segment in `dir`. Unreadable or outdated segments are
+    /// deleted; their rollouts are indexed again by the next refresh.
+    fn load(dir: PathBuf) -> Result<Self, String> {
+        let mut index = Self::new(dir);
+        let entries = std::fs::read_dir(&index.dir)
+            .map_err(|e| format!("Failed to read search index: {}", e))?;
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let segment = read_to_string(&path)
+                .ok()
+                .and_then(|s| serde_json::from_str::<Segment>(&s).ok())
+                .filter(|segment| segment.version == INDEX_VERSION);
+            match segment {
+                Some(segment) => index.insert(segment.id, segment.file, segment.postings),
+                None => {
+                    eprintln!("Discarding unreadable search index segment {:?}", path);
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+        Ok(index)
+    }
+
+    fn segment_path(&self, file_id: u32) -> PathBuf {
+        self.dir.join(format!("{}.json", file_id))
+    }
+
+    fn insert(&mut self, file_id: u32, mut file: IndexedFile, postings: Vec<(String, Posting)>) {
+        let mut terms = HashSet::new();
+        for (term, posting) in postings {
+            self.postings.entry(term.clone()).or_default().push(posting);
+            terms.insert(term);
+        }
+        file.terms = terms.into_iter().collect();
+        self.total_docs += file.docs;
+        self.total_length += file.length;
+        self.next_file_id = self.next_file_id.max(file_id + 1);
+        self.ids_by_path.insert(file.path.clone(), file_id);
+        self.files.insert(file_id, file);
+    }
+
+    fn remove_path(&mut self, path: &str) -> bool {
+        let Some(file_id) = self.ids_by_path.remove(path) else {
+            return false;
+        };
+        if let Some(file) = self.files.remove(&file_id) {
+            for term in &file.terms {
+                if let Some(postings) = self.postings.get_mut(term) {
+                    postings.retain(|p| p.file != file_id);
+                    if postings.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+            self.total_docs -= file.docs;
+            self.total_length -= file.length;
+        }
+        let segment_path = self.segment_path(file_id);
+        if let Err(e) = std::fs::remove_file(&segment_path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Failed to remove {:?}: {}", segment_path, e);
+            }
+        }
+        true
+    }
+
+    fn index_file(&mut self, stale: CatalogFile) -> Result<(), String> {
+        self.remove_path(&stale.path);
+
+        let file_id = self.next_file_id;
+        let mut conversation_id = String::new();
+        let mut docs = 0u64;
+        let mut total_length = 0u64;
+        let mut postings: Vec<(String, Posting)> = Vec::new();
+
+        for (event_index, event) in open_session_events(&stale.path)?.enumerate() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if event.event_type == "session_meta" {
+                conversation_id = event.message_id.clone().unwrap_or_default();
+            }
+            let Some(text) = searchable_text(&event) else {
+                continue;
+            };
+
+            let mut frequencies: HashMap<String, u32> = HashMap::new();
+            let mut length = 0u32;
+            for token in tokenize(text) {
+                *frequencies.entry(token).or_default() += 1;
+                length += 1;
+            }
+            if length == 0 {
+                continue;
+            }
+
+            for (term, tf) in frequencies {
+                let posting = Posting {
+                    file: file_id,
+                    event: event_index as u32,
+                    tf,
+                    len: length,
+                };
+                postings.push((term, posting));
+            }
+            docs += 1;
+            total_length += length as u64;
+        }
+
+        let segment = Segment {
+            version: INDEX_VERSION,
+            id: file_id,
+            file: IndexedFile {
+                path: stale.path,
+                conversation_id,
+                cwd: stale.cwd,
+                size_bytes: stale.size_bytes,
+                modified_at: stale.modified_at,
+                docs,
+                length: total_length,
+                terms: Vec::new(),
+            },
+            postings,
+        };
+        let json_str = serde_json::to_string(&segment)
+            .map_err(|e| format!("Failed to serialize search index segment: {}", e))?;
+        write_atomic(&self.segment_path(file_id), json_str.as_bytes())?;
+        self.insert(file_id, segment.file, segment.postings);
+        Ok(())
+    }
+
+    /// Indexes catalogued sessions that are new or changed since they were
+    /// indexed and drops files that are no longer catalogued.
+    fn refresh(&mut self, catalogued: Vec<CatalogFile>) {
+        let paths: HashSet<&str> = catalogued.iter().map(|file| file.path.as_str()).collect();
+        let removed: Vec<String> = self
+            .ids_by_path
+            .keys()
+            .filter(|path| !paths.contains(path.as_str()))
+            .cloned()
+            .collect();
+        for path in removed {
+            self.remove_path(&path);
+        }
+
+        for file in catalogued {
+            let indexed = self
+                .ids_by_path
+                .get(&file.path)
+                .and_then(|id| self.files.get(id));
+            let current = indexed.is_some_and(|indexed| {
+                indexed.size_bytes == file.size_bytes && indexed.modified_at == file.modified_at
+            });
+            if current {
+                continue;
+            }
+            let path = file.path.clone();
+            if let Err(e) = self.index_file(file) {
+                eprintln!("Failed to index {}: {}", path, e);
+            }
+        }
+    }
+
+    /// BM25 ranking of events against the query terms.
+    fn query(
+        &self,
+        terms: &[String],
+        project_path: Option<&str>,
+        limit: usize,
+    ) -> Vec<(u32, u32, f64)> {
+        if self.total_docs == 0 {
+            return Vec::new();
+        }
+        let total_docs = self.total_docs as f64;
+        let avg_length = self.total_length as f64 / total_docs;
+
+        let mut scores: HashMap<(u32, u32), f64> = HashMap::new();
+        for term in terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let df = postings.len() as f64;
+            let idf = ((total_docs - df + 0.5) / (df + 0.5) + 1.0).ln();
+            for posting in postings {
+                if let Some(project) = project_path {
+                    let cwd = self.files.get(&posting.file).and_then(|f| f.cwd.as_deref());
+                    if cwd != Some(project) {
+                        continue;
+                    }
+                }
+                let tf = posting.tf as f64;
+                let norm = 1.0 - BM25_B + BM25_B * posting.len as f64 / avg_length;
+                *scores.entry((posting.file, posting.event)).or_default() +=
+                    idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm);
+            }
+        }
+
+        let mut ranked: Vec<(u32, u32, f64)> = scores
+            .into_iter()
+            .map(|((file, event), score)| (file, event, score))
+            .collect();
+        ranked.sort_by(|a, b| b.2.total_cmp(&a.2));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+/// Every readable session in the catalog. Read before taking the index lock,
+/// so that a catalog refresh never runs while searches wait on the index.
+fn catalogued_sessions() -> Result<Vec<CatalogFile>, String> {
+    with_catalog(|catalog| {
+        catalog
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.session.is_some())
+            .map(|(path, entry)| CatalogFile {
+                path: path.clone(),
+                cwd: entry.cwd.clone(),
+                size_bytes: entry.size_bytes,
+                modified_at: entry.modified_at,
+            })
+            .collect()
+    })
+}
+
+fn with_index<T>(f: impl FnOnce(&mut SearchIndex) -> Result<T, String>) -> Result<T, String> {
+    let mut guard = SEARCH_INDEX
+        .lock()
+        .map_err(|e| format!("Search index lock poisoned: {}", e))?;
+    if guard.is_none() {
+        *guard = Some(SearchIndex::load(get_index_dir()?)?);
+    }
+    f(guard.as_mut().expect("search index loaded above"))
+}
+
+/// Drops deleted rollouts from the search index.
+pub fn remove_from_index(session_paths: &[String]) -> Result<(), String> {
+    with_index(|index| {
+        for path in session_paths {
+            index.remove_path(path);
+        }
+        Ok(())
+    })
+}
+
+/// Builds snippets for the ranked events, reading each matched rollout once.
+fn build_hits(
+    index: &SearchIndex,
+    ranked: Vec<(u32, u32, f64)>,
+    terms: &[String],
+) -> Result<Vec<RankedSearchHit>, String> {
+    let pattern = terms
+        .iter()
+        .map(|t| regex::escape(t))
+        .collect::<Vec<_>>()
+        .join("|");
+    let matcher = build_matcher(&format!("(?i){}", pattern), SearchMode::Regex)?;
+
+    let mut wanted: HashMap<u32, HashSet<u32>> = HashMap::new();
+    for (file, event, _) in &ranked {
+        wanted.entry(*file).or_default().insert(*event);
+    }
+
+    let mut snippets = HashMap::new();
+    for (file_id, events) in wanted {
+        let Some(file) = index.files.get(&file_id) else {
+            continue;
+        };
+        let stream = match open_session_events(&file.path) {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", file.path, e);
+                continue;
+            }
+        };
+        for (event_index, event) in stream.enumerate() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if !events.contains(&(event_index as u32)) {
+                continue;
+            }
+            let Some(text) = searchable_text(&event) else {
+                continue;
+            };
+            let snippet = match matcher.find(text) {
+                Some(found) => make_snippet(text, found.start(), found.end()),
+                None => make_snippet(text, 0, 0),
+            };
+            snippets.insert(
+                (file_id, event_index as u32),
+                (event.id, event.kind, snippet),
+            );
+        }
+    }
+
+    Ok(ranked
+        .into_iter()
+        .filter_map(|(file_id, event, score)| {
+            let file = index.files.get(&file_id)?;
+            let (event_id, kind, snippet) = snippets.remove(&(file_id, event))?;
+            Some(RankedSearchHit {
+                hit: SearchHit {
+                    path: file.path.clone(),
+                    conversation_id: file.conversation_id.clone(),
+                    event_index: event as usize,
+                    event_id,
+                    kind,
+                    snippet,
+                },
+                score,
+            })
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn search_sessions_ranked(
+    query: String,
+    project_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<RankedSearchHit>, String> {
+    let mut terms: Vec<String> = tokenize(&query).collect();
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let catalogued = catalogued_sessions()?;
+    with_index(|index| {
+        index.refresh(catalogued);
+        let ranked = index.query(
+            &terms,
+            project_path.as_deref(),
+            limit.unwrap_or(DEFAULT_RANKED_LIMIT),
+        );
+        build_hits(index, ranked, &terms)
+    })
+}
+
+#[tauri::command]
+pub async fn rebuild_search_index() -> Result<(), String> {
+    let catalogued = catalogued_sessions()?;
+    with_index(|index| {
+        let paths: Vec<String> = index.ids_by_path.keys().cloned().collect();
+        for path in &paths {
+            index.remove_path(path);
+        }
+        index.refresh(catalogued);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    fn catalog_file(path: &std::path::Path) -> CatalogFile {
+        CatalogFile {
+            path: path.to_string_lossy().to_string(),
+            cwd: Some("/home/me/app".to_string()),
+            size_bytes: ROLLOUT.len() as u64,
+            modified_at: None,
+        }
+    }
+
+    fn segment_count(dir: &std::path::Path) -> usize {
+        std::fs::read_dir(dir).unwrap().count()
+    }
+
+    #[test]
+    fn segments_round_trip_and_are_removed_with_their_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_dir = dir.path().join(INDEX_DIR_NAME);
+        std::fs::create_dir(&index_dir).unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        std::fs::write(&a, ROLLOUT).unwrap();
+        std::fs::write(&b, ROLLOUT.replace("migration", "deployment")).unwrap();
+
+        let mut index = SearchIndex::new(index_dir.clone());
+        index.index_file(catalog_file(&a)).unwrap();
+        index.index_file(catalog_file(&b)).unwrap();
+        assert_eq!(segment_count(&index_dir), 2);
+        let terms = vec!["migration".to_string()];
+        let ranked = index.query(&terms, None, 10);
+        assert!(!ranked.is_empty());
+
+        let loaded = SearchIndex::load(index_dir.clone()).unwrap();
+        assert_eq!(loaded.total_docs, index.total_docs);
+        assert_eq!(loaded.total_length, index.total_length);
+        assert_eq!(loaded.query(&terms, None, 10), ranked);
+        assert!(loaded.query(&terms, Some("/somewhere/else"), 10).is_empty());
+
+        // Indexing a file again replaces its segment.
+        index.index_file(catalog_file(&a)).unwrap();
+        assert_eq!(segment_count(&index_dir), 2);
+
+        assert!(index.remove_path(&a.to_string_lossy()));
+        assert_eq!(segment_count(&index_dir), 1);
+        assert!(index.query(&terms, None, 10).is_empty());
+        let loaded = SearchIndex::load(index_dir).unwrap();
+        assert_eq!(loaded.files.len(), 1);
+        assert_eq!(loaded.next_file_id, 2);
+    }
+
+    #[test]
+    fn unreadable_segments_are_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("0.json"), "{\"version\": 1").unwrap();
+        let index = SearchIndex::load(dir.path().to_path_buf()).unwrap();
+        assert!(index.files.is_empty());
+        assert_eq!(segment_count(dir.path()), 0);
+    }
+
+    #[test]
+    fn refresh_drops_uncatalogued_files_and_reindexes_changed_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_dir = dir.path().join(INDEX_DIR_NAME);
+        std::fs::create_dir(&index_dir).unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        std::fs::write(&a, ROLLOUT).unwrap();
+        std::fs::write(&b, ROLLOUT).unwrap();
+        let terms = vec!["migration".to_string()];
+        let files_with_hits = |index: &SearchIndex| {
+            let files: HashSet<u32> = index
+                .query(&terms, None, 100)
+                .into_iter()
+                .map(|(file, _, _)| file)
+                .collect();
+            files.len()
+        };
+
+        let mut index = SearchIndex::new(index_dir.clone());
+        index.refresh(vec![catalog_file(&a), catalog_file(&b)]);
+        assert_eq!(index.files.len(), 2);
+        assert_eq!(files_with_hits(&index), 2);
+
+        // A deleted rollout disappears from results and from disk.
+        index.refresh(vec![catalog_file(&b)]);
+        assert_eq!(index.files.len(), 1);
+        assert_eq!(segment_count(&index_dir), 1);
+        assert_eq!(files_with_hits(&index), 1);
+
+        // A rollout whose size changed is read again.
+        std::fs::write(&b, ROLLOUT.replace("migration", "deployment")).unwrap();
+        let mut changed = catalog_file(&b);
+        changed.size_bytes += 1;
+        index.refresh(vec![changed]);
+        assert_eq!(index.files.len(), 1);
+        assert_eq!(files_with_hits(&index), 0);
+    }
+
+    #[test]
+    fn events_matching_more_and_rarer_terms_rank_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let message = |text: &str| {
+            format!(
+                "{{\"type\":\"event_msg\",\"payload\":{{\"type\":\"user_message\",\"message\":\"{}\"}}}}\n",
+                text
+            )
+        };
+        let mut contents =
+            "{\"type\":\"session_meta\",\"payload\":{\"id\":\"0199-abc\"}}\n".to_string();
+        for text in [
+            "the build is broken again",
+            "flaky migration in the build",
+            "the tests pass",
+            "migration migration migration",
+            "the docs are out of date",
+        ] {
+            contents.push_str(&message(text));
+        }
+        std::fs::write(&path, contents).unwrap();
+
+        let mut index = SearchIndex::new(dir.path().join(INDEX_DIR_NAME));
+        std::fs::create_dir(&index.dir).unwrap();
+        index.index_file(catalog_file(&path)).unwrap();
+
+        let terms = vec!["build".to_string(), "migration".to_string()];
+        let ranked = index.query(&terms, None, 10);
+        let events: Vec<u32> = ranked.iter().map(|(_, event, _)| *event).collect();
+        assert_eq!(events, vec![2, 4, 1]);
+        assert!(ranked.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+
+        let ranked = index.query(&terms, None, 1);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].1, 2);
+    }
+}
@@ -0,0 +1,205 @@
+use super::cache::CachedSession;
+use super::get::get_app_data_dir;
+use super::store::{with_file_lock, write_atomic};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+const METADATA_FILE_NAME: &str = "session_metadata.json";
+pub const METADATA_SCHEMA_VERSION: u32 = 1;
+
+/// User-provided data about a session. Kept out of the session catalog so it
+/// survives rescans and catalog rebuilds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub starred: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl SessionMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.tags.is_empty() && !self.starred && self.notes.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataStore {
+    pub schema_version: u32,
+    /// Keyed by conversation id, so entries follow a session when its file moves.
+    #[serde(default)]
+    pub sessions: HashMap<String, SessionMetadata>,
+}
+
+impl Default for MetadataStore {
+    fn default() -> Self {
+        MetadataStore {
+            schema_version: METADATA_SCHEMA_VERSION,
+            sessions: HashMap::new(),
+        }
+    }
+}
+
+/// A cached session together with its metadata, as returned to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEntry {
+    #[serde(flatten)]
+    pub session: CachedSession,
+    #[serde(flatten)]
+    pub metadata: SessionMetadata,
+}
+
+pub fn get_metadata_path() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join(METADATA_FILE_NAME))
+}
+
+/// What to do with a metadata file that does not parse.
+#[derive(Clone, Copy, PartialEq)]
+enum OnCorrupt {
+    /// Read it as empty and leave it for the next locked write to deal with.
+    Ignore,
+    /// Move it aside to `.json.corrupt`. Only safe under the file lock.
+    Quarantine,
+}
+
+fn read_metadata(metadata_path: &Path, on_corrupt: OnCorrupt) -> Result<MetadataStore, String> {
+    if !metadata_path.exists() {
+        return Ok(MetadataStore::default());
+    }
+    let metadata_str = read_to_string(metadata_path)
+        .map_err(|e| format!("Failed to read session metadata: {}", e))?;
+    match serde_json::from_str::<MetadataStore>(&metadata_str) {
+        Ok(store) => Ok(store),
+        Err(e) if on_corrupt == OnCorrupt::Ignore => {
+            eprintln!("Failed to parse session metadata: {}", e);
+            Ok(MetadataStore::default())
+        }
+        Err(e) => {
+            // Unlike the caches this cannot be rebuilt, so keep the broken file around.
+            let backup_path = metadata_path.with_extension("json.corrupt");
+            eprintln!(
+                "Failed to parse session metadata ({}), moving it to {:?}",
+                e, backup_path
+            );
+            std::fs::rename(metadata_path, &backup_path)
+                .map_err(|e| format!("Failed to back up session metadata: {}", e))?;
+            Ok(MetadataStore::default())
+        }
+    }
+}
+
+pub fn load_metadata() -> Result<MetadataStore, String> {
+    load_metadata_at(&get_metadata_path()?)
+}
+
+/// Reads the store without taking its lock. A store written by a newer
+/// version is read as far as this version understands it.
+pub fn load_metadata_at(metadata_path: &Path) -> Result<MetadataStore, String> {
+    read_metadata(metadata_path, OnCorrupt::Ignore)
+}
+
+/// Read-modify-write of the metadata store under its lock. Entries left
+/// empty by `f` are dropped. A store written by a newer version is left
+/// alone, since rewriting it would drop what this version does not know.
+pub fn update_metadata<T>(
+    f: impl FnOnce(&mut MetadataStore) -> Result<T, String>,
+) -> Result<T, String> {
+    update_metadata_at(&get_metadata_path()?, f)
+}
+
+pub fn update_metadata_at<T>(
+    metadata_path: &Path,
+    f: impl FnOnce(&mut MetadataStore) -> Result<T, String>,
+) -> Result<T, String> {
+    with_file_lock(metadata_path, || {
+        let mut store = read_metadata(metadata_path, OnCorrupt::Quarantine)?;
+        if store.schema_version > METADATA_SCHEMA_VERSION {
+            return Err(format!(
+                "Session metadata uses schema version {}, but this version of the app only knows {}",
+                store.schema_version, METADATA_SCHEMA_VERSION
+            ));
+        }
+        store.schema_version = METADATA_SCHEMA_VERSION;
+        let result = f(&mut store)?;
+        store.sessions.retain(|_, metadata| !metadata.is_empty());
+        let json_str = serde_json::to_string_pretty(&store)
+            .map_err(|e| format!("Failed to serialize session metadata: {}", e))?;
+        write_atomic(metadata_path, json_str.as_bytes())?;
+        Ok(result)
+    })
+}
+
+pub fn with_metadata(sessions: Vec<CachedSession>) -> Result<Vec<SessionEntry>, String> {
+    let store = load_metadata()?;
+    Ok(sessions
+        .into_iter()
+        .map(|session| {
+            let metadata = store
+                .sessions
+                .get(&session.conversation_id)
+                .cloned()
+                .unwrap_or_default();
+            SessionEntry { session, metadata }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star(metadata_path: &Path, conversation_id: &str) -> Result<(), String> {
+        update_metadata_at(metadata_path, |store| {
+            store
+                .sessions
+                .entry(conversation_id.to_string())
+                .or_default()
+                .starred = true;
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn corrupt_stores_are_only_moved_aside_by_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata_path = dir.path().join(METADATA_FILE_NAME);
+        let corrupt_path = dir.path().join("session_metadata.json.corrupt");
+        std::fs::write(&metadata_path, "{\"schemaVersion\": ").unwrap();
+
+        let store = load_metadata_at(&metadata_path).unwrap();
+        assert!(store.sessions.is_empty());
+        assert!(metadata_path.exists());
+        assert!(!corrupt_path.exists());
+
+        star(&metadata_path, "0199-abc").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&corrupt_path).unwrap(),
+            "{\"schemaVersion\": "
+        );
+        let store = load_metadata_at(&metadata_path).unwrap();
+        assert!(store.sessions["0199-abc"].starred);
+    }
+
+    #[test]
+    fn newer_stores_are_read_but_not_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata_path = dir.path().join(METADATA_FILE_NAME);
+        let newer =
+            r#"{"schema_version": 2, "sessions": {"0199-abc": {"title": "Kept", "color": "red"}}}"#;
+        std::fs::write(&metadata_path, newer).unwrap();
+
+        let store = load_metadata_at(&metadata_path).unwrap();
+        assert_eq!(store.sessions["0199-abc"].title.as_deref(), Some("Kept"));
+
+        let error = star(&metadata_path, "0199-abc").unwrap_err();
+        assert!(error.contains("schema version 2"), "{}", error);
+        assert_eq!(std::fs::read_to_string(&metadata_path).unwrap(), newer);
+    }
+}
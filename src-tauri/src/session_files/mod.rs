@@ -1,7 +1,30 @@
+pub mod archive;
+pub mod cache;
+pub mod catalog;
 pub mod delete;
+pub mod events;
+pub mod export;
+pub mod export_html;
 pub mod file;
+pub mod follow;
 pub mod get;
+pub mod identity;
+pub mod import;
+pub mod index;
+pub mod metadata;
+pub mod overview;
+pub mod pricing;
+pub mod quarantine;
+pub mod redact;
+pub mod relocate;
+pub mod retention;
 pub mod save;
 pub mod scan;
+pub mod search;
+pub mod store;
+pub mod summary;
+pub mod trash;
 pub mod update;
+pub mod usage;
 pub mod utils;
+pub mod watch;
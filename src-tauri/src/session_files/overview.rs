@@ -0,0 +1,200 @@
+use super::catalog::{with_catalog, CatalogEntry, SessionCatalog};
+use super::quarantine::{classify_entry, load_cleanup_settings, CleanupSettings};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSort {
+    #[default]
+    Recency,
+    Size,
+    Sessions,
+    Tokens,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectOverview {
+    pub path: String,
+    pub session_count: usize,
+    pub first_activity: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub total_tokens: u64,
+    pub total_tool_calls: u64,
+    pub disk_usage_bytes: u64,
+}
+
+/// Aggregates a project's catalogued sessions; disk usage is the size of
+/// their rollouts when they were last catalogued.
+fn project_overview(project_path: &str, entries: &[&CatalogEntry]) -> ProjectOverview {
+    let mut overview = ProjectOverview {
+        path: project_path.to_string(),
+        session_count: entries.len(),
+        first_activity: None,
+        last_activity: None,
+        total_tokens: 0,
+        total_tool_calls: 0,
+        disk_usage_bytes: 0,
+    };
+
+    for entry in entries {
+        let Some(session) = &entry.session else {
+            continue;
+        };
+        let summary = &session.summary;
+        if let Some(started_at) = summary.started_at {
+            overview.first_activity = Some(match overview.first_activity {
+                Some(first) => first.min(started_at),
+                None => started_at,
+            });
+        }
+        if let Some(last_event_at) = summary.last_event_at.or(summary.started_at) {
+            overview.last_activity = overview.last_activity.max(Some(last_event_at));
+        }
+        overview.total_tokens += summary.total_tokens.unwrap_or(0);
+        overview.total_tool_calls += summary.tool_calls as u64;
+        overview.disk_usage_bytes += entry.size_bytes;
+    }
+
+    overview
+}
+
+/// One overview per project, counting the same sessions `scan_projects`
+/// lists: short or broken rollouts are left for the quarantine.
+fn project_overviews(catalog: &SessionCatalog, settings: &CleanupSettings) -> Vec<ProjectOverview> {
+    let mut projects: HashMap<&str, Vec<&CatalogEntry>> = HashMap::new();
+    for (path, entry) in &catalog.entries {
+        if entry.session.is_none() || classify_entry(path, entry, settings).is_some() {
+            continue;
+        }
+        if let Some(cwd) = entry.cwd.as_deref() {
+            projects.entry(cwd).or_default().push(entry);
+        }
+    }
+    projects
+        .into_iter()
+        .map(|(project_path, entries)| project_overview(project_path, &entries))
+        .collect()
+}
+
+fn sort_overviews(overviews: &mut [ProjectOverview], sort_by: ProjectSort) {
+    match sort_by {
+        ProjectSort::Recency => overviews.sort_by_key(|o| Reverse(o.last_activity)),
+        ProjectSort::Size => overviews.sort_by_key(|o| Reverse(o.disk_usage_bytes)),
+        ProjectSort::Sessions => overviews.sort_by_key(|o| Reverse(o.session_count)),
+        ProjectSort::Tokens => overviews.sort_by_key(|o| Reverse(o.total_tokens)),
+    }
+}
+
+#[tauri::command]
+pub async fn get_projects_overview(
+    sort_by: Option<ProjectSort>,
+) -> Result<Vec<ProjectOverview>, String> {
+    let settings = load_cleanup_settings()?;
+    let mut overviews = with_catalog(|catalog| project_overviews(catalog, &settings))?;
+    sort_overviews(&mut overviews, sort_by.unwrap_or_default());
+    Ok(overviews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_files::cache::CachedSession;
+    use crate::session_files::summary::SessionSummary;
+    use chrono::{Duration, TimeZone};
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 9, 20, hour, 0, 0).unwrap()
+    }
+
+    fn entry(cwd: &str, hour: u32, tokens: u64, size_bytes: u64) -> CatalogEntry {
+        CatalogEntry {
+            cwd: Some(cwd.to_string()),
+            git_remote: None,
+            modified_at: Some(at(hour)),
+            size_bytes,
+            inode: None,
+            line_count: 10,
+            has_session_meta: true,
+            session: Some(CachedSession {
+                path: String::new(),
+                conversation_id: format!("{}-{}", cwd, hour),
+                preview: String::new(),
+                summary: SessionSummary {
+                    started_at: Some(at(hour)),
+                    last_event_at: Some(at(hour) + Duration::minutes(30)),
+                    tool_calls: 2,
+                    total_tokens: Some(tokens),
+                    ..SessionSummary::default()
+                },
+            }),
+        }
+    }
+
+    fn catalog(entries: Vec<(&str, CatalogEntry)>) -> SessionCatalog {
+        let mut catalog = SessionCatalog::default();
+        for (path, entry) in entries {
+            catalog.entries.insert(path.to_string(), entry);
+        }
+        catalog
+    }
+
+    #[test]
+    fn projects_aggregate_their_sessions() {
+        let catalog = catalog(vec![
+            ("/s/a1.jsonl", entry("/app", 9, 100, 1_000)),
+            ("/s/a2.jsonl", entry("/app", 11, 50, 500)),
+            ("/s/b1.jsonl", entry("/lib", 10, 1_000, 100)),
+        ]);
+        let mut overviews = project_overviews(&catalog, &CleanupSettings::default());
+        sort_overviews(&mut overviews, ProjectSort::Recency);
+
+        let app = &overviews[0];
+        assert_eq!(app.path, "/app");
+        assert_eq!(app.session_count, 2);
+        assert_eq!(app.first_activity, Some(at(9)));
+        assert_eq!(app.last_activity, Some(at(11) + Duration::minutes(30)));
+        assert_eq!(app.total_tokens, 150);
+        assert_eq!(app.total_tool_calls, 4);
+        assert_eq!(app.disk_usage_bytes, 1_500);
+        assert_eq!(overviews[1].path, "/lib");
+
+        let order = |sort_by| {
+            let mut overviews = overviews.clone();
+            sort_overviews(&mut overviews, sort_by);
+            overviews.into_iter().map(|o| o.path).collect::<Vec<_>>()
+        };
+        assert_eq!(order(ProjectSort::Size), vec!["/app", "/lib"]);
+        assert_eq!(order(ProjectSort::Sessions), vec!["/app", "/lib"]);
+        assert_eq!(order(ProjectSort::Tokens), vec!["/lib", "/app"]);
+    }
+
+    #[test]
+    fn cleanup_candidates_are_left_out_like_in_scan_projects() {
+        let mut short = entry("/app", 10, 1_000, 300);
+        short.line_count = 2;
+        let mut broken = entry("/app", 10, 1_000, 300);
+        broken.has_session_meta = false;
+        let mut empty = entry("/gone", 10, 0, 0);
+        empty.size_bytes = 0;
+        let mut unreadable = entry("/app", 10, 0, 300);
+        unreadable.session = None;
+        let catalog = catalog(vec![
+            ("/s/ok.jsonl", entry("/app", 9, 100, 1_000)),
+            ("/s/short.jsonl", short),
+            ("/s/broken.jsonl", broken),
+            ("/s/empty.jsonl", empty),
+            ("/s/unreadable.jsonl", unreadable),
+        ]);
+
+        let overviews = project_overviews(&catalog, &CleanupSettings::default());
+
+        assert_eq!(overviews.len(), 1);
+        assert_eq!(overviews[0].session_count, 1);
+        assert_eq!(overviews[0].total_tokens, 100);
+        assert_eq!(overviews[0].disk_usage_bytes, 1_000);
+    }
+}
@@ -0,0 +1,155 @@
+use super::get::get_app_data_dir;
+use super::store::{with_file_lock, write_atomic};
+use super::usage::TokenUsage;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+const PRICING_FILE_NAME: &str = "pricing.json";
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub cached_input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    fn new(input: f64, cached_input: f64, output: f64) -> Self {
+        ModelPrice {
+            input_per_million: input,
+            cached_input_per_million: cached_input,
+            output_per_million: output,
+        }
+    }
+
+    /// Reasoning tokens are part of `output_tokens`, so they are not billed separately.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let uncached_input = usage.input_tokens.saturating_sub(usage.cached_input_tokens);
+        (uncached_input as f64 * self.input_per_million
+            + usage.cached_input_tokens as f64 * self.cached_input_per_million
+            + usage.output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Prices keyed by model name. A model without an exact entry uses the
+/// entry for its name without a release date or version suffix, e.g.
+/// `gpt-4.1-2025-04-14` is priced as `gpt-4.1`. Anything else is unpriced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTable {
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        let models = [
+            ("gpt-5", ModelPrice::new(1.25, 0.125, 10.0)),
+            ("gpt-5-codex", ModelPrice::new(1.25, 0.125, 10.0)),
+            ("gpt-5-mini", ModelPrice::new(0.25, 0.025, 2.0)),
+            ("gpt-5-nano", ModelPrice::new(0.05, 0.005, 0.4)),
+            ("gpt-4.1", ModelPrice::new(2.0, 0.5, 8.0)),
+            ("o3", ModelPrice::new(2.0, 0.5, 8.0)),
+            ("o4-mini", ModelPrice::new(1.1, 0.275, 4.4)),
+            ("codex-mini-latest", ModelPrice::new(1.5, 0.375, 6.0)),
+        ];
+        PriceTable {
+            models: models
+                .into_iter()
+                .map(|(name, price)| (name.to_string(), price))
+                .collect(),
+        }
+    }
+}
+
+impl PriceTable {
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.models
+            .get(model)
+            .or_else(|| self.models.get(strip_release_suffix(model)?))
+    }
+
+    pub fn cost(&self, model: Option<&str>, usage: &TokenUsage) -> Option<f64> {
+        self.price_for(model?).map(|price| price.cost(usage))
+    }
+}
+
+/// `model` without a trailing `-YYYY-MM-DD` release date or numeric version,
+/// or `None` if it has neither.
+fn strip_release_suffix(model: &str) -> Option<&str> {
+    static SUFFIX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"-(?:\d{4}-\d{2}-\d{2}|\d+)$").expect("valid regex"));
+    let suffix = SUFFIX.find(model)?;
+    Some(&model[..suffix.start()])
+}
+
+pub fn get_pricing_path() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join(PRICING_FILE_NAME))
+}
+
+pub fn load_price_table() -> Result<PriceTable, String> {
+    let pricing_path = get_pricing_path()?;
+    if !pricing_path.exists() {
+        return Ok(PriceTable::default());
+    }
+    let pricing_str =
+        read_to_string(&pricing_path).map_err(|e| format!("Failed to read price table: {}", e))?;
+    serde_json::from_str(&pricing_str).map_err(|e| format!("Failed to parse price table: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_price_table() -> Result<PriceTable, String> {
+    load_price_table()
+}
+
+#[tauri::command]
+pub async fn set_price_table(table: PriceTable) -> Result<(), String> {
+    let pricing_path = get_pricing_path()?;
+    let json_str = serde_json::to_string_pretty(&table)
+        .map_err(|e| format!("Failed to serialize price table: {}", e))?;
+    with_file_lock(&pricing_path, || {
+        write_atomic(&pricing_path, json_str.as_bytes())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_price(table: &PriceTable, model: &str) -> Option<f64> {
+        table.price_for(model).map(|price| price.input_per_million)
+    }
+
+    #[test]
+    fn price_for_strips_only_release_suffixes() {
+        let table = PriceTable::default();
+        assert_eq!(input_price(&table, "gpt-5-mini"), Some(0.25));
+        assert_eq!(input_price(&table, "gpt-4.1-2025-04-14"), Some(2.0));
+        assert_eq!(input_price(&table, "gpt-5-mini-20250807"), Some(0.25));
+        assert_eq!(input_price(&table, "o3-0613"), Some(2.0));
+        // No prefix matching: these are different models.
+        assert_eq!(input_price(&table, "gpt-5-codex-high"), None);
+        assert_eq!(input_price(&table, "gpt-4.1-mini"), None);
+        assert_eq!(input_price(&table, "o3-pro"), None);
+        assert_eq!(input_price(&table, "gpt-6"), None);
+    }
+
+    #[test]
+    fn cost_bills_cached_input_separately() {
+        let price = ModelPrice::new(1.0, 0.1, 10.0);
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            cached_input_tokens: 500_000,
+            output_tokens: 100_000,
+            reasoning_output_tokens: 50_000,
+            total_tokens: 1_100_000,
+        };
+        let cost = price.cost(&usage);
+        assert!((cost - (0.5 + 0.05 + 1.0)).abs() < 1e-9, "{}", cost);
+    }
+}
@@ -0,0 +1,614 @@
+use super::catalog::{with_catalog, CatalogEntry};
+use super::file::get_sessions_path;
+use super::get::get_app_data_dir;
+use super::save::sync_session_files;
+use super::store::{with_file_lock, write_atomic};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+const QUARANTINE_DIR_NAME: &str = "quarantine";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const SETTINGS_FILE_NAME: &str = "cleanup_settings.json";
+pub const QUARANTINE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CleanupSettings {
+    /// Rollouts with fewer lines than this are cleanup candidates.
+    pub min_lines: usize,
+    /// Rollouts modified more recently than this may still be written by a
+    /// running `codex` process and are never candidates.
+    pub active_grace_minutes: i64,
+    /// Quarantined files are purged once they have been quarantined this long.
+    pub retention_days: i64,
+}
+
+impl Default for CleanupSettings {
+    fn default() -> Self {
+        CleanupSettings {
+            min_lines: 4,
+            active_grace_minutes: 60,
+            retention_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupReason {
+    Empty,
+    TooShort,
+    /// The first line is not a `session_meta` record with a session id.
+    Unparseable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupCandidate {
+    pub path: String,
+    pub reason: CleanupReason,
+    pub line_count: usize,
+    pub size_bytes: u64,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineEntry {
+    /// File name inside the quarantine directory.
+    pub id: String,
+    pub original_path: String,
+    pub reason: CleanupReason,
+    pub size_bytes: u64,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// Result for one file of `quarantine_sessions`. Exactly one of `entry` and
+/// `error` is set.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineOutcome {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<QuarantineEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl QuarantineOutcome {
+    fn ok(path: String, entry: QuarantineEntry) -> Self {
+        QuarantineOutcome {
+            path,
+            entry: Some(entry),
+            error: None,
+        }
+    }
+
+    fn failed(path: String, error: String) -> Self {
+        QuarantineOutcome {
+            path,
+            entry: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Result for one id of `restore_quarantined`. Exactly one of `path` (where
+/// the file was restored to) and `error` is set.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreOutcome {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineManifest {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub entries: Vec<QuarantineEntry>,
+}
+
+impl Default for QuarantineManifest {
+    fn default() -> Self {
+        QuarantineManifest {
+            schema_version: QUARANTINE_SCHEMA_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+pub fn get_quarantine_dir() -> Result<PathBuf, String> {
+    let quarantine_dir = get_app_data_dir()?.join(QUARANTINE_DIR_NAME);
+    std::fs::create_dir_all(&quarantine_dir)
+        .map_err(|e| format!("Failed to create quarantine dir: {}", e))?;
+    Ok(quarantine_dir)
+}
+
+pub fn load_cleanup_settings() -> Result<CleanupSettings, String> {
+    let settings_path = get_app_data_dir()?.join(SETTINGS_FILE_NAME);
+    if !settings_path.exists() {
+        return Ok(CleanupSettings::default());
+    }
+    let settings_str = read_to_string(&settings_path)
+        .map_err(|e| format!("Failed to read cleanup settings: {}", e))?;
+    serde_json::from_str(&settings_str)
+        .map_err(|e| format!("Failed to parse cleanup settings: {}", e))
+}
+
+fn read_manifest(manifest_path: &Path) -> Result<QuarantineManifest, String> {
+    if !manifest_path.exists() {
+        return Ok(QuarantineManifest::default());
+    }
+    let manifest_str = read_to_string(manifest_path)
+        .map_err(|e| format!("Failed to read quarantine manifest: {}", e))?;
+    let manifest: QuarantineManifest = serde_json::from_str(&manifest_str)
+        .map_err(|e| format!("Failed to parse quarantine manifest: {}", e))?;
+    if manifest.schema_version > QUARANTINE_SCHEMA_VERSION {
+        return Err(format!(
+            "Quarantine manifest version {} is newer than supported version {}",
+            manifest.schema_version, QUARANTINE_SCHEMA_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Read-modify-write of the manifest in `quarantine_dir` under its lock. File
+/// moves happen inside `f`, so the manifest and the quarantine directory
+/// change together.
+fn update_manifest<T>(
+    quarantine_dir: &Path,
+    f: impl FnOnce(&mut QuarantineManifest) -> Result<T, String>,
+) -> Result<T, String> {
+    let manifest_path = quarantine_dir.join(MANIFEST_FILE_NAME);
+    with_file_lock(&manifest_path, || {
+        let mut manifest = read_manifest(&manifest_path)?;
+        let result = f(&mut manifest);
+        // Write even if `f` failed part way, so moves it already made are recorded.
+        let json_str = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize quarantine manifest: {}", e))?;
+        write_atomic(&manifest_path, json_str.as_bytes())?;
+        result
+    })
+}
+
+/// Why `path` should be cleaned up, or `None` if it looks like a real session
+/// or may still be in use.
+pub fn classify(path: &Path, settings: &CleanupSettings) -> Option<CleanupCandidate> {
+    let entry = CatalogEntry::read(path)?;
+    classify_entry(&path.to_string_lossy(), &entry, settings)
+}
+
+/// `classify` for a file already read into the session catalog.
+pub fn classify_entry(
+    path: &str,
+    entry: &CatalogEntry,
+    settings: &CleanupSettings,
+) -> Option<CleanupCandidate> {
+    let grace_cutoff = Utc::now() - Duration::minutes(settings.active_grace_minutes);
+    if entry
+        .modified_at
+        .is_none_or(|modified| modified > grace_cutoff)
+    {
+        return None;
+    }
+
+    let reason = if entry.size_bytes == 0 {
+        CleanupReason::Empty
+    } else if !entry.has_session_meta {
+        CleanupReason::Unparseable
+    } else if entry.line_count < settings.min_lines {
+        CleanupReason::TooShort
+    } else {
+        return None;
+    };
+
+    Some(CleanupCandidate {
+        path: path.to_string(),
+        reason,
+        line_count: entry.line_count,
+        size_bytes: entry.size_bytes,
+        modified_at: entry.modified_at,
+    })
+}
+
+pub fn find_candidates(settings: &CleanupSettings) -> Result<Vec<CleanupCandidate>, String> {
+    with_catalog(|catalog| {
+        catalog
+            .entries
+            .iter()
+            .filter_map(|(path, entry)| classify_entry(path, entry, settings))
+            .collect()
+    })
+}
+
+/// Removes quarantined files older than the retention period.
+pub fn purge_expired(settings: &CleanupSettings) -> Result<Vec<QuarantineEntry>, String> {
+    purge_expired_in(&get_quarantine_dir()?, settings)
+}
+
+fn purge_expired_in(
+    quarantine_dir: &Path,
+    settings: &CleanupSettings,
+) -> Result<Vec<QuarantineEntry>, String> {
+    let cutoff = Utc::now() - Duration::days(settings.retention_days);
+    update_manifest(quarantine_dir, |manifest| {
+        let mut purged = Vec::new();
+        let mut kept = Vec::new();
+        for entry in manifest.entries.drain(..) {
+            if entry.quarantined_at > cutoff {
+                kept.push(entry);
+                continue;
+            }
+            match std::fs::remove_file(quarantine_dir.join(&entry.id)) {
+                Ok(()) => purged.push(entry),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => purged.push(entry),
+                Err(e) => {
+                    eprintln!("Failed to purge quarantined file {}: {}", entry.id, e);
+                    kept.push(entry);
+                }
+            }
+        }
+        manifest.entries = kept;
+        Ok(purged)
+    })
+}
+
+#[tauri::command]
+pub async fn find_cleanup_candidates() -> Result<Vec<CleanupCandidate>, String> {
+    find_candidates(&load_cleanup_settings()?)
+}
+
+/// Moves the rollouts in `session_paths` that are inside `sessions_dir` and
+/// still cleanup candidates into `quarantine_dir`. Returns the outcome for
+/// each path and the paths that were moved.
+fn quarantine_in(
+    quarantine_dir: &Path,
+    sessions_dir: &Path,
+    settings: &CleanupSettings,
+    session_paths: &[String],
+) -> Result<(Vec<QuarantineOutcome>, Vec<String>), String> {
+    update_manifest(quarantine_dir, |manifest| {
+        let mut outcomes = Vec::new();
+        let mut removed = Vec::new();
+        for session_path in session_paths {
+            let path = Path::new(session_path);
+            let inside = std::fs::canonicalize(path)
+                .is_ok_and(|resolved| resolved.starts_with(sessions_dir));
+            if !inside {
+                outcomes.push(QuarantineOutcome::failed(
+                    session_path.clone(),
+                    format!("{} is not inside the sessions directory", session_path),
+                ));
+                continue;
+            }
+            let Some(candidate) = classify(path, settings) else {
+                outcomes.push(QuarantineOutcome::failed(
+                    session_path.clone(),
+                    format!("{} is no longer a cleanup candidate", session_path),
+                ));
+                continue;
+            };
+
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let quarantined_at = Utc::now();
+            let id = format!("{}-{}", quarantined_at.timestamp_millis(), file_name);
+            if let Err(e) = std::fs::rename(path, quarantine_dir.join(&id)) {
+                outcomes.push(QuarantineOutcome::failed(
+                    session_path.clone(),
+                    format!("Failed to quarantine {}: {}", session_path, e),
+                ));
+                continue;
+            }
+
+            let entry = QuarantineEntry {
+                id,
+                original_path: candidate.path,
+                reason: candidate.reason,
+                size_bytes: candidate.size_bytes,
+                quarantined_at,
+            };
+            manifest.entries.push(entry.clone());
+            outcomes.push(QuarantineOutcome::ok(session_path.clone(), entry));
+            removed.push(session_path.clone());
+        }
+        Ok((outcomes, removed))
+    })
+}
+
+/// Moves the given rollouts into quarantine. Each path is re-checked, so a
+/// file that has grown or been touched since it was listed is left alone.
+/// Every file is attempted; failures are reported per file.
+#[tauri::command]
+pub async fn quarantine_sessions(
+    session_paths: Vec<String>,
+) -> Result<Vec<QuarantineOutcome>, String> {
+    let settings = load_cleanup_settings()?;
+    let sessions_dir = std::fs::canonicalize(get_sessions_path()?)
+        .map_err(|e| format!("Failed to resolve sessions dir: {}", e))?;
+
+    let (outcomes, removed) = quarantine_in(
+        &get_quarantine_dir()?,
+        &sessions_dir,
+        &settings,
+        &session_paths,
+    )?;
+
+    if let Err(e) = sync_session_files(&removed) {
+        eprintln!("Failed to update session catalog: {}", e);
+    }
+    Ok(outcomes)
+}
+
+#[tauri::command]
+pub async fn list_quarantine() -> Result<Vec<QuarantineEntry>, String> {
+    let manifest_path = get_quarantine_dir()?.join(MANIFEST_FILE_NAME);
+    Ok(read_manifest(&manifest_path)?.entries)
+}
+
+/// Moves one quarantined file back to its original path, unless that path
+/// has been reused.
+fn restore_entry(quarantine_dir: &Path, entry: &QuarantineEntry) -> Result<PathBuf, String> {
+    let original_path = PathBuf::from(&entry.original_path);
+    if original_path.exists() {
+        return Err(format!("{:?} already exists", original_path));
+    }
+    if let Some(parent) = original_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    std::fs::rename(quarantine_dir.join(&entry.id), &original_path)
+        .map_err(|e| format!("Failed to restore {}: {}", entry.id, e))?;
+    Ok(original_path)
+}
+
+/// Restores each of `ids` from `quarantine_dir`. Returns the outcome for each
+/// id and the paths that were restored.
+fn restore_in(
+    quarantine_dir: &Path,
+    ids: &[String],
+) -> Result<(Vec<RestoreOutcome>, Vec<PathBuf>), String> {
+    update_manifest(quarantine_dir, |manifest| {
+        let mut outcomes = Vec::new();
+        let mut restored = Vec::new();
+        for id in ids {
+            let result = manifest
+                .entries
+                .iter()
+                .position(|entry| &entry.id == id)
+                .ok_or_else(|| format!("No quarantined file with id {}", id))
+                .and_then(|index| {
+                    let path = restore_entry(quarantine_dir, &manifest.entries[index])?;
+                    manifest.entries.remove(index);
+                    Ok(path)
+                });
+            outcomes.push(match result {
+                Ok(path) => {
+                    restored.push(path.clone());
+                    RestoreOutcome {
+                        id: id.clone(),
+                        path: Some(path.to_string_lossy().to_string()),
+                        error: None,
+                    }
+                }
+                Err(error) => RestoreOutcome {
+                    id: id.clone(),
+                    path: None,
+                    error: Some(error),
+                },
+            });
+        }
+        Ok((outcomes, restored))
+    })
+}
+
+/// Moves quarantined files back to their original paths. Every id is
+/// attempted; a file whose original path has been reused stays quarantined
+/// and is reported as failed.
+#[tauri::command]
+pub async fn restore_quarantined(ids: Vec<String>) -> Result<Vec<RestoreOutcome>, String> {
+    let (outcomes, restored) = restore_in(&get_quarantine_dir()?, &ids)?;
+    if let Err(e) = sync_session_files(&restored) {
+        eprintln!("Failed to update session catalog: {}", e);
+    }
+    Ok(outcomes)
+}
+
+#[tauri::command]
+pub async fn purge_quarantine() -> Result<Vec<QuarantineEntry>, String> {
+    purge_expired(&load_cleanup_settings()?)
+}
+
+#[tauri::command]
+pub async fn get_cleanup_settings() -> Result<CleanupSettings, String> {
+    load_cleanup_settings()
+}
+
+#[tauri::command]
+pub async fn set_cleanup_settings(settings: CleanupSettings) -> Result<(), String> {
+    let settings_path = get_app_data_dir()?.join(SETTINGS_FILE_NAME);
+    let json_str = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize cleanup settings: {}", e))?;
+    with_file_lock(&settings_path, || {
+        write_atomic(&settings_path, json_str.as_bytes())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    /// Writes `contents` to `path` and backdates it past the active grace period.
+    fn write_idle(path: &Path, contents: &str) {
+        std::fs::write(path, contents).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - std::time::Duration::from_secs(2 * 3600))
+            .unwrap();
+    }
+
+    fn reason(path: &Path) -> Option<CleanupReason> {
+        classify(path, &CleanupSettings::default()).map(|candidate| candidate.reason)
+    }
+
+    struct Dirs {
+        _root: tempfile::TempDir,
+        sessions: PathBuf,
+        quarantine: PathBuf,
+    }
+
+    fn dirs() -> Dirs {
+        let root = tempfile::tempdir().unwrap();
+        let sessions = std::fs::canonicalize(root.path()).unwrap().join("sessions");
+        let quarantine = root.path().join("quarantine");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::create_dir_all(&quarantine).unwrap();
+        Dirs {
+            _root: root,
+            sessions,
+            quarantine,
+        }
+    }
+
+    fn quarantine(dirs: &Dirs, paths: &[&Path]) -> Vec<QuarantineOutcome> {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        let (outcomes, _) = quarantine_in(
+            &dirs.quarantine,
+            &dirs.sessions,
+            &CleanupSettings::default(),
+            &paths,
+        )
+        .unwrap();
+        outcomes
+    }
+
+    #[test]
+    fn classify_reports_why_a_file_is_a_candidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let first_line = ROLLOUT.lines().next().unwrap();
+        let cases = [
+            ("empty.jsonl", String::new(), Some(CleanupReason::Empty)),
+            (
+                "short.jsonl",
+                format!("{}\n", first_line),
+                Some(CleanupReason::TooShort),
+            ),
+            (
+                "junk.jsonl",
+                "not json\n".repeat(10),
+                Some(CleanupReason::Unparseable),
+            ),
+            ("real.jsonl", ROLLOUT.to_string(), None),
+        ];
+        for (name, contents, expected) in cases {
+            let path = dir.path().join(name);
+            write_idle(&path, &contents);
+            assert_eq!(reason(&path), expected, "{}", name);
+        }
+
+        // A file still being written is never a candidate.
+        let active = dir.path().join("active.jsonl");
+        std::fs::write(&active, "").unwrap();
+        assert_eq!(reason(&active), None);
+    }
+
+    #[test]
+    fn quarantine_moves_only_candidates_inside_the_sessions_dir() {
+        let dirs = dirs();
+        let empty = dirs.sessions.join("empty.jsonl");
+        let real = dirs.sessions.join("real.jsonl");
+        let outside = dirs.quarantine.join("../outside.jsonl");
+        write_idle(&empty, "");
+        write_idle(&real, ROLLOUT);
+        write_idle(&outside, "");
+
+        let outcomes = quarantine(&dirs, &[&empty, &real, &outside]);
+        assert!(outcomes[0].entry.is_some());
+        assert!(outcomes[1].error.as_ref().unwrap().contains("no longer"));
+        assert!(outcomes[2].error.as_ref().unwrap().contains("not inside"));
+
+        assert!(!empty.exists() && real.exists() && outside.exists());
+        let entry = outcomes[0].entry.as_ref().unwrap();
+        assert_eq!(entry.reason, CleanupReason::Empty);
+        assert!(dirs.quarantine.join(&entry.id).exists());
+        let manifest = read_manifest(&dirs.quarantine.join(MANIFEST_FILE_NAME)).unwrap();
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.entries[0].original_path, empty.to_string_lossy());
+    }
+
+    #[test]
+    fn restore_reports_each_id_and_keeps_going() {
+        let dirs = dirs();
+        let a = dirs.sessions.join("a.jsonl");
+        let b = dirs.sessions.join("b.jsonl");
+        write_idle(&a, "");
+        write_idle(&b, "");
+        let outcomes = quarantine(&dirs, &[&a, &b]);
+        let ids: Vec<String> = outcomes
+            .iter()
+            .map(|outcome| outcome.entry.as_ref().unwrap().id.clone())
+            .collect();
+        // `a` was recreated meanwhile, so it cannot be restored.
+        std::fs::write(&a, "new").unwrap();
+
+        let request = vec!["missing".to_string(), ids[0].clone(), ids[1].clone()];
+        let (outcomes, restored) = restore_in(&dirs.quarantine, &request).unwrap();
+        assert!(outcomes[0]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("No quarantined file"));
+        assert!(outcomes[1]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("already exists"));
+        assert_eq!(outcomes[2].path.as_deref(), Some(&*b.to_string_lossy()));
+        assert_eq!(restored, vec![b.clone()]);
+
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "new");
+        assert!(b.exists());
+        let manifest = read_manifest(&dirs.quarantine.join(MANIFEST_FILE_NAME)).unwrap();
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.entries[0].id, ids[0]);
+    }
+
+    #[test]
+    fn purge_removes_only_expired_files() {
+        let dirs = dirs();
+        let old = dirs.sessions.join("old.jsonl");
+        let new = dirs.sessions.join("new.jsonl");
+        write_idle(&old, "");
+        write_idle(&new, "");
+        quarantine(&dirs, &[&old, &new]);
+        update_manifest(&dirs.quarantine, |manifest| {
+            manifest.entries[0].quarantined_at = Utc::now() - Duration::days(31);
+            Ok(())
+        })
+        .unwrap();
+
+        let purged = purge_expired_in(&dirs.quarantine, &CleanupSettings::default()).unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].original_path, old.to_string_lossy());
+        assert!(!dirs.quarantine.join(&purged[0].id).exists());
+
+        let manifest = read_manifest(&dirs.quarantine.join(MANIFEST_FILE_NAME)).unwrap();
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.entries[0].original_path, new.to_string_lossy());
+        assert!(dirs.quarantine.join(&manifest.entries[0].id).exists());
+    }
+}
@@ -0,0 +1,240 @@
+use super::catalog::with_catalog;
+use super::quarantine::load_cleanup_settings;
+use super::save::sync_session_files;
+use super::store::write_atomic;
+use crate::config::rename_project_in_config;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocationFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocationReport {
+    pub rewritten: Vec<String>,
+    pub failed: Vec<RelocationFailure>,
+    /// Whether `config.toml` was updated. It is left alone while some
+    /// sessions still point at the old path, so that they can be retried.
+    pub config_updated: bool,
+}
+
+/// Rewrites `payload.cwd` of a JSON line if it equals `old_path`. Returns
+/// `None` when the line is left untouched.
+fn rewrite_cwd(line: &str, old_path: &str, new_path: &str) -> Option<String> {
+    let mut value: Value = serde_json::from_str(line).ok()?;
+    if value["payload"]["cwd"].as_str() != Some(old_path) {
+        return None;
+    }
+    value["payload"]["cwd"] = Value::String(new_path.to_string());
+    serde_json::to_string(&value).ok()
+}
+
+/// Rewrites the `session_meta` line of a rollout, and `turn_context` lines if
+/// asked to. Other lines are copied byte for byte. The new contents replace
+/// the file atomically, which would cut off a `codex` process still appending
+/// to the old file, so rollouts modified after `grace_cutoff` are refused.
+fn relocate_file(
+    path: &Path,
+    old_path: &str,
+    new_path: &str,
+    rewrite_turn_context: bool,
+    grace_cutoff: DateTime<Utc>,
+) -> Result<(), String> {
+    let modified: Option<DateTime<Utc>> = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(Into::into);
+    if modified.is_none_or(|modified| modified > grace_cutoff) {
+        return Err(format!(
+            "{:?} was modified recently and may still be in use by codex",
+            path
+        ));
+    }
+
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+
+    let mut rewritten = String::with_capacity(contents.len());
+    for (index, line) in contents.split_inclusive('\n').enumerate() {
+        let body = line.trim_end_matches(['\n', '\r']);
+        let should_rewrite =
+            index == 0 || (rewrite_turn_context && body.contains("\"turn_context\""));
+        match should_rewrite
+            .then(|| rewrite_cwd(body, old_path, new_path))
+            .flatten()
+        {
+            Some(new_line) => {
+                rewritten.push_str(&new_line);
+                rewritten.push_str(&line[body.len()..]);
+            }
+            None => rewritten.push_str(line),
+        }
+    }
+
+    let current_len = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?
+        .len();
+    if current_len != contents.len() as u64 {
+        return Err(format!("{:?} changed while it was being rewritten", path));
+    }
+    write_atomic(path, rewritten.as_bytes())
+}
+
+/// Points every session of `old_path` at `new_path` after a checkout has
+/// been moved or renamed: rewrites the rollouts, updates the session catalog and
+/// renames the project in `~/.codex/config.toml` once no session is left
+/// behind. Rollouts modified within the cleanup settings'
+/// `active_grace_minutes` are reported as failed.
+#[tauri::command]
+pub async fn relocate_project(
+    old_path: String,
+    new_path: String,
+    rewrite_turn_context: Option<bool>,
+) -> Result<RelocationReport, String> {
+    if old_path == new_path {
+        return Err("The old and new project paths are the same".to_string());
+    }
+    let rewrite_turn_context = rewrite_turn_context.unwrap_or(false);
+    let settings = load_cleanup_settings()?;
+    let grace_cutoff = Utc::now() - Duration::minutes(settings.active_grace_minutes);
+    let mut report = RelocationReport::default();
+
+    let paths: Vec<String> = with_catalog(|catalog| {
+        catalog
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.cwd.as_deref() == Some(old_path.as_str()))
+            .map(|(path, _)| path.clone())
+            .collect()
+    })?;
+
+    for path_str in paths {
+        match relocate_file(
+            Path::new(&path_str),
+            &old_path,
+            &new_path,
+            rewrite_turn_context,
+            grace_cutoff,
+        ) {
+            Ok(()) => report.rewritten.push(path_str),
+            Err(error) => report.failed.push(RelocationFailure {
+                path: path_str,
+                error,
+            }),
+        }
+    }
+
+    sync_session_files(&report.rewritten)?;
+    if report.failed.is_empty() {
+        report.config_updated = rename_project_in_config(&old_path, &new_path)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    const META: &str = r#"{"timestamp":"2025-09-20T10:00:00Z","type":"session_meta","payload":{"id":"0199-abc","cwd":"/old"}}"#;
+    const CONTEXT: &str =
+        r#"{"timestamp":"2025-09-20T10:00:01Z","type":"turn_context","payload":{"cwd":"/old"}}"#;
+    const MESSAGE: &str = r#"{"timestamp":"2025-09-20T10:00:02Z",  "type":"event_msg","payload":{"type":"user_message","message":"cd /old","cwd":"/old"}}"#;
+
+    fn rollout(dir: &Path, newline: &str) -> std::path::PathBuf {
+        let path = dir.join("rollout.jsonl");
+        let contents = [META, CONTEXT, MESSAGE].join(newline) + newline;
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn lines(path: &Path) -> Vec<Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn cutoff_after_now() -> DateTime<Utc> {
+        Utc::now() + Duration::minutes(1)
+    }
+
+    #[test]
+    fn rewrite_cwd_only_touches_matching_lines() {
+        let rewritten = rewrite_cwd(META, "/old", "/new").unwrap();
+        let value: Value = serde_json::from_str(&rewritten).unwrap();
+        assert_eq!(value["payload"]["cwd"], "/new");
+        assert_eq!(value["payload"]["id"], "0199-abc");
+
+        assert_eq!(rewrite_cwd(META, "/other", "/new"), None);
+        assert_eq!(rewrite_cwd("not json", "/old", "/new"), None);
+    }
+
+    #[test]
+    fn only_the_first_line_is_rewritten_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = rollout(dir.path(), "\n");
+
+        relocate_file(&path, "/old", "/new", false, cutoff_after_now()).unwrap();
+
+        let lines = lines(&path);
+        assert_eq!(lines[0]["payload"]["cwd"], "/new");
+        assert_eq!(lines[1]["payload"]["cwd"], "/old");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.ends_with(&format!("{}\n", MESSAGE)));
+    }
+
+    #[test]
+    fn turn_context_lines_are_rewritten_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = rollout(dir.path(), "\n");
+
+        relocate_file(&path, "/old", "/new", true, cutoff_after_now()).unwrap();
+
+        let lines = lines(&path);
+        assert_eq!(lines[0]["payload"]["cwd"], "/new");
+        assert_eq!(lines[1]["payload"]["cwd"], "/new");
+        assert_eq!(lines[2]["payload"]["cwd"], "/old");
+    }
+
+    #[test]
+    fn crlf_line_endings_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = rollout(dir.path(), "\r\n");
+
+        relocate_file(&path, "/old", "/new", true, cutoff_after_now()).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.matches("\r\n").count(), 3);
+        assert_eq!(contents.matches('\n').count(), 3);
+        assert!(contents.ends_with(&format!("{}\r\n", MESSAGE)));
+    }
+
+    #[test]
+    fn recently_modified_rollouts_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = rollout(dir.path(), "\n");
+        let before = std::fs::read(&path).unwrap();
+        let modified: DateTime<Utc> = SystemTime::now().into();
+
+        let error = relocate_file(
+            &path,
+            "/old",
+            "/new",
+            false,
+            modified - Duration::minutes(5),
+        )
+        .unwrap_err();
+
+        assert!(error.contains("may still be in use"), "{}", error);
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+    }
+}
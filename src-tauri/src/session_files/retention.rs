@@ -0,0 +1,380 @@
+use super::cache::CachedSession;
+use super::get::get_app_data_dir;
+use super::metadata::{with_metadata, SessionEntry};
+use super::quarantine::load_cleanup_settings;
+use super::scan::{scan_project_sessions, sessions_by_project};
+use super::store::{with_file_lock, write_atomic};
+use super::trash::{move_to_trash, TrashOutcome};
+use super::utils::extract_datetime;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+const RETENTION_FILE_NAME: &str = "retention.json";
+
+/// A session matches a rule when every condition that is set holds, e.g.
+/// `{ "keepLatest": 200 }` or `{ "olderThanDays": 7, "maxToolCalls": 0 }`.
+/// Starred sessions only match rules that set `includeStarred`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetentionRule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Only sessions after the newest `keep_latest` of their project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_latest: Option<usize>,
+    /// Only sessions whose last activity is older than this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub older_than_days: Option<i64>,
+    /// Only sessions with at most this many tool calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<u32>,
+    pub include_starred: bool,
+}
+
+impl RetentionRule {
+    /// A rule without conditions would match every session.
+    fn has_condition(&self) -> bool {
+        self.keep_latest.is_some()
+            || self.older_than_days.is_some()
+            || self.max_tool_calls.is_some()
+    }
+
+    fn matches(&self, entry: &SessionEntry, rank: usize, now: DateTime<Utc>) -> bool {
+        if !self.has_condition() {
+            return false;
+        }
+        if entry.metadata.starred && !self.include_starred {
+            return false;
+        }
+        if self.keep_latest.is_some_and(|keep| rank < keep) {
+            return false;
+        }
+        if let Some(days) = self.older_than_days {
+            match last_activity(entry) {
+                Some(last) if last < now - Duration::days(days) => {}
+                _ => return false,
+            }
+        }
+        if self
+            .max_tool_calls
+            .is_some_and(|max| entry.session.summary.tool_calls > max)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub rules: Vec<RetentionRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionCandidate {
+    pub path: String,
+    pub project_path: String,
+    pub conversation_id: String,
+    /// Index of the first rule that matched.
+    pub rule: usize,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPlan {
+    pub candidates: Vec<RetentionCandidate>,
+    /// Freed once the trash is emptied.
+    pub reclaimed_bytes: u64,
+}
+
+fn last_activity(entry: &SessionEntry) -> Option<DateTime<Utc>> {
+    let summary = &entry.session.summary;
+    summary
+        .last_event_at
+        .or(summary.started_at)
+        .or_else(|| extract_datetime(&entry.session.path).map(|dt| dt.and_utc()))
+}
+
+pub fn get_retention_path() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join(RETENTION_FILE_NAME))
+}
+
+pub fn load_retention_policy() -> Result<RetentionPolicy, String> {
+    let retention_path = get_retention_path()?;
+    if !retention_path.exists() {
+        return Ok(RetentionPolicy::default());
+    }
+    let retention_str = read_to_string(&retention_path)
+        .map_err(|e| format!("Failed to read retention policy: {}", e))?;
+    serde_json::from_str(&retention_str)
+        .map_err(|e| format!("Failed to parse retention policy: {}", e))
+}
+
+/// Sessions of one project that the policy would remove. `sessions` must be
+/// ordered newest first, as `scan_project_sessions` returns them. Rollouts
+/// modified within `active_grace` may still be written by Codex and are
+/// never matched.
+pub fn evaluate_project(
+    policy: &RetentionPolicy,
+    project_path: &str,
+    sessions: &[SessionEntry],
+    now: DateTime<Utc>,
+    active_grace: Duration,
+) -> Vec<RetentionCandidate> {
+    sessions
+        .iter()
+        .enumerate()
+        .filter_map(|(rank, entry)| {
+            let rule = policy
+                .rules
+                .iter()
+                .position(|rule| rule.matches(entry, rank, now))?;
+            let metadata = std::fs::metadata(&entry.session.path).ok()?;
+            let modified: Option<DateTime<Utc>> = metadata.modified().ok().map(Into::into);
+            if modified.is_none_or(|modified| modified > now - active_grace) {
+                return None;
+            }
+            Some(RetentionCandidate {
+                path: entry.session.path.clone(),
+                project_path: project_path.to_string(),
+                conversation_id: entry.session.conversation_id.clone(),
+                rule,
+                last_activity: last_activity(entry),
+                size_bytes: metadata.len(),
+            })
+        })
+        .collect()
+}
+
+/// Evaluates the stored policy against one project, or every project.
+pub fn plan_retention(project_path: Option<String>) -> Result<RetentionPlan, String> {
+    let policy = load_retention_policy()?;
+    let active_grace = Duration::minutes(load_cleanup_settings()?.active_grace_minutes);
+    let mut plan = RetentionPlan::default();
+    if policy.rules.is_empty() {
+        return Ok(plan);
+    }
+
+    let projects: Vec<(String, Vec<CachedSession>)> = match project_path {
+        Some(project_path) => {
+            let sessions = scan_project_sessions(&project_path)?;
+            vec![(project_path, sessions)]
+        }
+        None => sessions_by_project()?.into_iter().collect(),
+    };
+    // Metadata is looked up for every project at once and split up again.
+    let (project_paths, sessions): (Vec<String>, Vec<Vec<CachedSession>>) =
+        projects.into_iter().unzip();
+    let counts: Vec<usize> = sessions.iter().map(Vec::len).collect();
+    let mut entries = with_metadata(sessions.into_iter().flatten().collect())?.into_iter();
+    let now = Utc::now();
+    for (project_path, count) in project_paths.iter().zip(counts) {
+        let sessions: Vec<SessionEntry> = entries.by_ref().take(count).collect();
+        plan.candidates.extend(evaluate_project(
+            &policy,
+            project_path,
+            &sessions,
+            now,
+            active_grace,
+        ));
+    }
+    plan.reclaimed_bytes = plan.candidates.iter().map(|c| c.size_bytes).sum();
+    Ok(plan)
+}
+
+#[tauri::command]
+pub async fn get_retention_policy() -> Result<RetentionPolicy, String> {
+    load_retention_policy()
+}
+
+#[tauri::command]
+pub async fn set_retention_policy(policy: RetentionPolicy) -> Result<(), String> {
+    if let Some(index) = policy.rules.iter().position(|rule| !rule.has_condition()) {
+        return Err(format!(
+            "Retention rule {} needs keepLatest, olderThanDays or maxToolCalls",
+            index + 1
+        ));
+    }
+    let retention_path = get_retention_path()?;
+    let json_str = serde_json::to_string_pretty(&policy)
+        .map_err(|e| format!("Failed to serialize retention policy: {}", e))?;
+    with_file_lock(&retention_path, || {
+        write_atomic(&retention_path, json_str.as_bytes())
+    })
+}
+
+/// Dry run: what `apply_retention_policy` would remove, without touching anything.
+#[tauri::command]
+pub async fn preview_retention_policy(
+    project_path: Option<String>,
+) -> Result<RetentionPlan, String> {
+    plan_retention(project_path)
+}
+
+/// Moves the previewed sessions (`session_paths`, from
+/// `preview_retention_policy`) to the trash, using the same path as
+/// `delete_sessions_files`. The policy is evaluated again first, and only
+/// sessions it still matches are removed, so a session that became active or
+/// starred after the preview is kept.
+#[tauri::command]
+pub async fn apply_retention_policy(
+    project_path: Option<String>,
+    session_paths: Vec<String>,
+) -> Result<Vec<TrashOutcome>, String> {
+    let previewed: HashSet<String> = session_paths.into_iter().collect();
+    let plan = plan_retention(project_path)?;
+    let mut by_project: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for candidate in plan
+        .candidates
+        .into_iter()
+        .filter(|candidate| previewed.contains(&candidate.path))
+    {
+        by_project
+            .entry(candidate.project_path)
+            .or_default()
+            .push(candidate.path);
+    }
+
+    let mut outcomes = Vec::new();
+    for (project_path, session_paths) in by_project {
+        outcomes.extend(move_to_trash(&project_path, &session_paths)?);
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_files::metadata::SessionMetadata;
+    use crate::session_files::summary::SessionSummary;
+    use std::path::Path;
+    use std::time::SystemTime;
+
+    const GRACE: Duration = Duration::minutes(60);
+
+    /// A session whose rollout was last written `age` ago and whose last
+    /// event was a little under `days_old` days ago.
+    fn session(dir: &Path, n: usize, age: Duration, days_old: i64) -> SessionEntry {
+        let path = dir.join(format!("rollout-{}.jsonl", n));
+        std::fs::write(&path, "{}\n").unwrap();
+        let modified = SystemTime::now() - age.to_std().unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        SessionEntry {
+            session: CachedSession {
+                path: path.to_string_lossy().to_string(),
+                conversation_id: format!("id-{}", n),
+                preview: String::new(),
+                summary: SessionSummary {
+                    last_event_at: Some(Utc::now() - Duration::days(days_old) + Duration::hours(1)),
+                    tool_calls: n as u32,
+                    ..Default::default()
+                },
+            },
+            metadata: SessionMetadata::default(),
+        }
+    }
+
+    /// Five idle sessions, newest first, with 0..5 tool calls and 0..5 days old.
+    fn sessions(dir: &Path) -> Vec<SessionEntry> {
+        (0..5)
+            .map(|n| session(dir, n, Duration::days(1), n as i64))
+            .collect()
+    }
+
+    fn matched(rule: RetentionRule, sessions: &[SessionEntry]) -> Vec<String> {
+        let policy = RetentionPolicy { rules: vec![rule] };
+        evaluate_project(&policy, "/project", sessions, Utc::now(), GRACE)
+            .into_iter()
+            .map(|candidate| candidate.conversation_id)
+            .collect()
+    }
+
+    #[test]
+    fn keep_latest_spares_the_newest_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = RetentionRule {
+            keep_latest: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(matched(rule, &sessions(dir.path())), vec!["id-3", "id-4"]);
+    }
+
+    #[test]
+    fn starred_sessions_are_exempt_unless_included() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sessions = sessions(dir.path());
+        sessions[4].metadata.starred = true;
+        let rule = RetentionRule {
+            keep_latest: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(matched(rule.clone(), &sessions), vec!["id-3"]);
+
+        let rule = RetentionRule {
+            include_starred: true,
+            ..rule
+        };
+        assert_eq!(matched(rule, &sessions), vec!["id-3", "id-4"]);
+    }
+
+    #[test]
+    fn older_than_days_uses_last_activity() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = RetentionRule {
+            older_than_days: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(matched(rule, &sessions(dir.path())), vec!["id-3", "id-4"]);
+    }
+
+    #[test]
+    fn max_tool_calls_combines_with_other_conditions() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = RetentionRule {
+            max_tool_calls: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(matched(rule, &sessions(dir.path())), vec!["id-0", "id-1"]);
+
+        let rule = RetentionRule {
+            max_tool_calls: Some(3),
+            older_than_days: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(matched(rule, &sessions(dir.path())), vec!["id-3"]);
+    }
+
+    #[test]
+    fn rule_without_conditions_matches_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = RetentionRule {
+            include_starred: true,
+            ..Default::default()
+        };
+        assert!(matched(rule, &sessions(dir.path())).is_empty());
+    }
+
+    #[test]
+    fn recently_modified_rollouts_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sessions = sessions(dir.path());
+        sessions[4] = session(dir.path(), 4, Duration::minutes(5), 4);
+        let rule = RetentionRule {
+            older_than_days: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(matched(rule, &sessions), vec!["id-3"]);
+    }
+}
@@ -1,11 +1,55 @@
-use super::file::{get_session_info, get_sessions_path, read_first_line};
-use super::utils::{count_lines, extract_datetime};
-use chrono::{DateTime, Utc};
+use super::cache::migrate_legacy_caches;
+use super::cache::{sort_sessions, CachedSession};
+use super::catalog::{get_catalog_path, refresh_catalog_with, with_catalog};
+use super::file::{get_sessions_path, SessionInfo};
+use super::metadata::{load_metadata, with_metadata, SessionEntry};
+use super::quarantine::{classify_entry, load_cleanup_settings, purge_expired};
+use super::summary::SessionSummary;
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
 use walkdir::WalkDir;
 
+pub const SCAN_PROGRESS_EVENT: &str = "session-scan-progress";
+/// Partial results are batched so a cold scan does not flood the webview.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+static RUNNING_SCANS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannedSession {
+    pub project_path: Option<String>,
+    #[serde(flatten)]
+    pub session: CachedSession,
+}
+
+/// Payload of `session-scan-progress`: the sessions found since the last
+/// event, and how many of the rollouts have been looked at so far.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub scan_id: String,
+    pub done: usize,
+    pub total: usize,
+    pub sessions: Vec<ScannedSession>,
+}
+
+/// What `start_session_scan` returns: the sessions found, with their
+/// metadata, unless the scan was cancelled.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanResult {
+    pub cancelled: bool,
+    pub sessions: Vec<SessionEntry>,
+}
+
 pub fn scan_jsonl_files<P: AsRef<Path>>(dir_path: P) -> impl Iterator<Item = walkdir::DirEntry> {
     WalkDir::new(dir_path)
         .into_iter()
@@ -14,102 +58,66 @@ pub fn scan_jsonl_files<P: AsRef<Path>>(dir_path: P) -> impl Iterator<Item = wal
         .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
 }
 
-pub fn scan_project_sessions_incremental(
-    project_path: &str,
-    after: Option<DateTime<Utc>>,
-) -> Result<Vec<Value>, String> {
-    let sessions_dir = get_sessions_path()?;
-    let mut results = Vec::new();
-
-    for entry in scan_jsonl_files(&sessions_dir) {
-        let path = entry.path();
-
-        // Skip files that haven't been modified since last scan
-        if let Some(cutoff) = after {
-            if let Ok(metadata) = std::fs::metadata(path) {
-                if let Ok(modified) = metadata.modified() {
-                    let modified_datetime: DateTime<Utc> = modified.into();
-                    if modified_datetime <= cutoff {
-                        continue; // Skip this file
-                    }
-                }
-            }
-        }
-
-        let file_path = path.to_string_lossy().to_string();
-        match read_first_line(path) {
-            Ok(line) => {
-                if let Ok(value) = serde_json::from_str::<Value>(&line) {
-                    if value["payload"]["cwd"].as_str() == Some(project_path) {
-                        if let Ok(info) = get_session_info(path) {
-                            let original_text = info.user_message.unwrap_or_default();
-                            let truncated_text: String = original_text.chars().take(50).collect();
-                            results.push(json!({
-                                "path": file_path,
-                                "conversationId": info.session_id,
-                                "preview": truncated_text
-                            }));
-                        }
-                    }
-                }
-            }
-            Err(e) => eprintln!("Failed to read first line: {}", e),
-        }
+/// Cache entry for a single rollout from what `summarize_session` read.
+pub fn cached_session(path: &Path, info: SessionInfo, summary: SessionSummary) -> CachedSession {
+    let original_text = info.user_message.unwrap_or_default();
+    let truncated_text: String = original_text.chars().take(50).collect();
+    CachedSession {
+        path: path.to_string_lossy().to_string(),
+        conversation_id: info.session_id,
+        preview: truncated_text,
+        summary,
     }
+}
 
-    results.sort_by(|a, b| {
-        let a_dt = extract_datetime(a["path"].as_str().unwrap_or_default());
-        let b_dt = extract_datetime(b["path"].as_str().unwrap_or_default());
-        match (a_dt, b_dt) {
-            (Some(a_dt), Some(b_dt)) => b_dt.cmp(&a_dt),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a["path"].as_str().cmp(&b["path"].as_str()),
-        }
-    });
-
+/// Sessions of a project, newest first. Served from the session catalog, so
+/// only the project's own entries are copied.
+pub fn scan_project_sessions(project_path: &str) -> Result<Vec<CachedSession>, String> {
+    let mut results = with_catalog(|catalog| catalog.project_sessions(project_path))?;
+    sort_sessions(&mut results);
     Ok(results)
 }
 
+/// Sessions of every project, each newest first, from one look at the
+/// session catalog.
+pub fn sessions_by_project() -> Result<HashMap<String, Vec<CachedSession>>, String> {
+    let mut projects: HashMap<String, Vec<CachedSession>> = with_catalog(|catalog| {
+        catalog
+            .entries_by_project()
+            .into_iter()
+            .map(|(project_path, entries)| {
+                let sessions = entries
+                    .into_iter()
+                    .filter_map(|entry| entry.session.clone())
+                    .collect();
+                (project_path.to_string(), sessions)
+            })
+            .collect()
+    })?;
+    for sessions in projects.values_mut() {
+        sort_sessions(sessions);
+    }
+    Ok(projects)
+}
+
 #[tauri::command]
 pub async fn scan_projects() -> Result<Vec<Value>, String> {
-    let sessions_dir = get_sessions_path()?;
-    let mut unique_projects = HashSet::new();
-
-    for entry in scan_jsonl_files(&sessions_dir) {
-        let file_path = entry.path().to_path_buf();
-
-        match count_lines(&file_path) {
-            Ok(line_count) if line_count < 4 => {
-                eprintln!("Deleting file with {} lines: {:?}", line_count, file_path);
-                if let Err(e) = std::fs::remove_file(&file_path) {
-                    eprintln!("Failed to delete file {:?}: {}", file_path, e);
-                }
-                continue;
-            }
-            Ok(_) => { /* File has enough lines, proceed */ }
-            Err(e) => {
-                eprintln!("Failed to count lines for {:?}: {}", file_path, e);
-                continue;
-            }
-        }
-
-        let mut project_path: Option<String> = None;
-        match read_first_line(file_path.clone()) {
-            Ok(line) => {
-                if let Ok(value) = serde_json::from_str::<Value>(&line) {
-                    if let Some(cwd) = value["payload"]["cwd"].as_str() {
-                        project_path = Some(cwd.to_string());
-                    }
-                }
-            }
-            Err(e) => eprintln!("Failed to read first line for {:?}: {}", file_path, e),
-        }
-
-        if let Some(cwd) = project_path {
-            unique_projects.insert(cwd);
-        }
+    let settings = load_cleanup_settings()?;
+    if let Err(e) = purge_expired(&settings) {
+        eprintln!("Failed to purge quarantine: {}", e);
+    }
+    if let Err(e) = migrate_legacy_caches() {
+        eprintln!("Failed to migrate legacy caches: {}", e);
     }
+    let unique_projects: HashSet<String> = with_catalog(|catalog| {
+        catalog
+            .entries
+            .iter()
+            // Short or broken rollouts are left for the quarantine to deal with.
+            .filter(|(path, entry)| classify_entry(path, entry, &settings).is_none())
+            .filter_map(|(_, entry)| entry.cwd.clone())
+            .collect()
+    })?;
 
     let results: Vec<Value> = unique_projects
         .into_iter()
@@ -123,3 +131,112 @@ pub async fn scan_projects() -> Result<Vec<Value>, String> {
 
     Ok(results)
 }
+
+/// Refreshes the session catalog, streaming the sessions it finds (only those
+/// of `project_path` and tagged with `tag`, if given) as
+/// `session-scan-progress` events, then returns them all, newest first. Starting a scan with the id of a running
+/// one cancels the old scan.
+#[tauri::command]
+pub async fn start_session_scan(
+    app: AppHandle,
+    scan_id: String,
+    project_path: Option<String>,
+    tag: Option<String>,
+) -> Result<ScanResult, String> {
+    let tagged: Option<HashSet<String>> = match &tag {
+        Some(tag) => Some(
+            load_metadata()?
+                .sessions
+                .into_iter()
+                .filter(|(_, metadata)| metadata.tags.contains(tag))
+                .map(|(conversation_id, _)| conversation_id)
+                .collect(),
+        ),
+        None => None,
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut scans = RUNNING_SCANS
+            .lock()
+            .map_err(|e| format!("Scan registry poisoned: {}", e))?;
+        if let Some(previous) = scans.insert(scan_id.clone(), cancel.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let mut found = Vec::new();
+    let mut pending = Vec::new();
+    let mut last_emit = Instant::now();
+    let emit = |pending: &mut Vec<ScannedSession>, done: usize, total: usize| {
+        let progress = ScanProgress {
+            scan_id: scan_id.clone(),
+            done,
+            total,
+            sessions: std::mem::take(pending),
+        };
+        if let Err(e) = app.emit(SCAN_PROGRESS_EVENT, progress) {
+            eprintln!("Failed to emit scan progress: {}", e);
+        }
+    };
+
+    let mut last_counts = (0, 0);
+    let sessions_dir = get_sessions_path()?;
+    let catalog_path = get_catalog_path()?;
+    let result = refresh_catalog_with(&sessions_dir, &catalog_path, &cancel, |progress| {
+        last_counts = (progress.done, progress.total);
+        let in_project = project_path.is_none() || progress.entry.cwd == project_path;
+        let wanted = progress.entry.session.as_ref().filter(|session| {
+            in_project
+                && tagged
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(&session.conversation_id))
+        });
+        if let Some(session) = wanted {
+            found.push(session.clone());
+            pending.push(ScannedSession {
+                project_path: progress.entry.cwd.clone(),
+                session: session.clone(),
+            });
+        }
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            emit(&mut pending, progress.done, progress.total);
+            last_emit = Instant::now();
+        }
+    });
+    let completed = result.as_ref().is_ok_and(|completed| *completed);
+    if completed {
+        emit(&mut pending, last_counts.0, last_counts.1);
+    }
+
+    if let Ok(mut scans) = RUNNING_SCANS.lock() {
+        // A newer scan may have taken over the id.
+        if scans.get(&scan_id).is_some_and(|c| Arc::ptr_eq(c, &cancel)) {
+            scans.remove(&scan_id);
+        }
+    }
+    result?;
+    if !completed {
+        return Ok(ScanResult {
+            cancelled: true,
+            sessions: Vec::new(),
+        });
+    }
+    sort_sessions(&mut found);
+    Ok(ScanResult {
+        cancelled: false,
+        sessions: with_metadata(found)?,
+    })
+}
+
+/// Stops a scan started with `start_session_scan`, e.g. when the user
+/// switches to another project. Returns whether it was still running.
+#[tauri::command]
+pub async fn cancel_session_scan(scan_id: String) -> Result<bool, String> {
+    let scans = RUNNING_SCANS
+        .lock()
+        .map_err(|e| format!("Scan registry poisoned: {}", e))?;
+    Ok(scans
+        .get(&scan_id)
+        .map(|cancel| cancel.store(true, Ordering::Relaxed))
+        .is_some())
+}
@@ -0,0 +1,255 @@
+use super::catalog::with_catalog;
+use super::events::{open_session_events, EventKind, SessionEvent};
+use regex::{Match, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+const DEFAULT_SEARCH_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    #[default]
+    Substring,
+    CaseInsensitive,
+    Regex,
+}
+
+/// Text around a match, split so the frontend can highlight `matched`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub path: String,
+    pub conversation_id: String,
+    pub event_index: usize,
+    pub event_id: String,
+    pub kind: EventKind,
+    pub snippet: Snippet,
+}
+
+pub fn build_matcher(query: &str, mode: SearchMode) -> Result<Regex, String> {
+    let (pattern, case_insensitive) = match mode {
+        SearchMode::Substring => (regex::escape(query), false),
+        SearchMode::CaseInsensitive => (regex::escape(query), true),
+        SearchMode::Regex => (query.to_string(), false),
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// The text of an event that is worth searching: user and agent messages,
+/// tool call inputs and tool outputs.
+pub fn searchable_text(event: &SessionEvent) -> Option<&str> {
+    match event.kind {
+        EventKind::User => event.text.as_deref(),
+        EventKind::Assistant if event.role.as_deref() == Some("assistant") => event.text.as_deref(),
+        EventKind::ToolCall => event.tool_input.as_deref(),
+        EventKind::ToolResult => event.tool_output.as_deref(),
+        _ => None,
+    }
+}
+
+/// The first match in `text` that is not empty. Patterns like `a*` match
+/// the empty string everywhere, which says nothing about the event.
+pub fn find_match<'t>(matcher: &Regex, text: &'t str) -> Option<Match<'t>> {
+    matcher.find_iter(text).find(|found| !found.is_empty())
+}
+
+pub fn make_snippet(text: &str, start: usize, end: usize) -> Snippet {
+    let before_start = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let after_end = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map(|(i, _)| end + i)
+        .unwrap_or(text.len());
+
+    let mut before = text[before_start..start].replace('\n', " ");
+    if before_start > 0 {
+        before.insert(0, '…');
+    }
+    let mut after = text[end..after_end].replace('\n', " ");
+    if after_end < text.len() {
+        after.push('…');
+    }
+
+    Snippet {
+        before,
+        matched: text[start..end].replace('\n', " "),
+        after,
+    }
+}
+
+/// Searches every event of a single rollout, returning one hit per matching
+/// event. Lines that cannot be read are skipped.
+pub fn search_session_file(path: &Path, matcher: &Regex) -> Result<Vec<SearchHit>, String> {
+    let file_path = path.to_string_lossy().to_string();
+    let mut conversation_id = String::new();
+    let mut hits = Vec::new();
+
+    for (event_index, event) in open_session_events(path)?.enumerate() {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("Skipping event {} of {:?}: {}", event_index, path, e);
+                continue;
+            }
+        };
+        if event.event_type == "session_meta" {
+            conversation_id = event.message_id.clone().unwrap_or_default();
+        }
+        let Some(text) = searchable_text(&event) else {
+            continue;
+        };
+        if let Some(found) = find_match(matcher, text) {
+            hits.push(SearchHit {
+                path: file_path.clone(),
+                conversation_id: conversation_id.clone(),
+                event_index,
+                event_id: event.id.clone(),
+                kind: event.kind,
+                snippet: make_snippet(text, found.start(), found.end()),
+            });
+        }
+    }
+
+    Ok(hits)
+}
+
+#[tauri::command]
+pub async fn search_sessions(
+    query: String,
+    mode: Option<SearchMode>,
+    project_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let matcher = build_matcher(&query, mode.unwrap_or_default())?;
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    let paths: Vec<String> = with_catalog(|catalog| {
+        catalog
+            .entries
+            .iter()
+            .filter(|(_, entry)| {
+                project_path.is_none() || entry.cwd.as_deref() == project_path.as_deref()
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    })?;
+    let mut results = Vec::new();
+
+    for path in paths {
+        let path = Path::new(&path);
+        match search_session_file(path, &matcher) {
+            Ok(hits) => results.extend(hits),
+            Err(e) => eprintln!("Failed to search {:?}: {}", path, e),
+        }
+        if results.len() >= limit {
+            results.truncate(limit);
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLLOUT: &str = include_str!("../../tests/fixtures/rollout.jsonl");
+
+    fn search(contents: &[u8], query: &str, mode: SearchMode) -> Vec<SearchHit> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        std::fs::write(&path, contents).unwrap();
+        search_session_file(&path, &build_matcher(query, mode).unwrap()).unwrap()
+    }
+
+    fn matched(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter()
+            .map(|hit| hit.snippet.matched.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn substring_search_is_literal_and_case_sensitive() {
+        let hits = search(ROLLOUT.as_bytes(), "migration", SearchMode::Substring);
+        assert_eq!(matched(&hits), vec!["migration", "migration"]);
+        assert!(hits.iter().all(|hit| hit.conversation_id == "0199-abc"));
+        assert_eq!(hits[0].kind, EventKind::User);
+        assert_eq!(hits[1].kind, EventKind::Assistant);
+
+        assert!(search(ROLLOUT.as_bytes(), "Migration", SearchMode::Substring).is_empty());
+        assert!(search(ROLLOUT.as_bytes(), "db.r.", SearchMode::Substring).is_empty());
+    }
+
+    #[test]
+    fn case_insensitive_search_keeps_the_original_text() {
+        let hits = search(ROLLOUT.as_bytes(), "FIXED THE", SearchMode::CaseInsensitive);
+        assert_eq!(matched(&hits), vec!["Fixed the"]);
+    }
+
+    #[test]
+    fn regex_search_skips_empty_matches() {
+        let hits = search(ROLLOUT.as_bytes(), r"mig\w+", SearchMode::Regex);
+        assert_eq!(
+            matched(&hits),
+            vec!["migration", "migrate", "migrate", "migration"]
+        );
+
+        // `§*` matches the empty string at the start of every event, and
+        // nothing else in the fixture.
+        assert!(search(ROLLOUT.as_bytes(), "§*", SearchMode::Regex).is_empty());
+        let hits = search(ROLLOUT.as_bytes(), "bug|z*", SearchMode::Regex);
+        assert!(hits.iter().all(|hit| !hit.snippet.matched.is_empty()));
+        assert_eq!(matched(&hits), vec!["bug"]);
+
+        assert!(build_matcher("(", SearchMode::Regex).is_err());
+    }
+
+    #[test]
+    fn unreadable_lines_are_skipped() {
+        let mut contents = b"\xff\xfe not utf-8\n".to_vec();
+        contents.extend_from_slice(ROLLOUT.as_bytes());
+        let hits = search(&contents, "migration", SearchMode::Substring);
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[test]
+    fn snippets_split_around_the_match() {
+        let text = format!("{}\nneedle\n{}", "a".repeat(100), "b".repeat(100));
+        let start = text.find("needle").unwrap();
+        let snippet = make_snippet(&text, start, start + "needle".len());
+        assert_eq!(snippet.before, format!("…{} ", "a".repeat(59)));
+        assert_eq!(snippet.matched, "needle");
+        assert_eq!(snippet.after, format!(" {}…", "b".repeat(59)));
+
+        let snippet = make_snippet("é needle é", 3, 9);
+        assert_eq!(
+            (
+                snippet.before.as_str(),
+                snippet.matched.as_str(),
+                snippet.after.as_str()
+            ),
+            ("é ", "needle", " é")
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+static FILE_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// A fresh temporary path next to `path`, on the same file system so it can
+/// be renamed over `path`.
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    sibling_path(
+        path,
+        &format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ),
+    )
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so readers never observe a partially written file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let temp_path = temp_path_for(path);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+
+    result.map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        format!("Failed to write {:?}: {}", path, e)
+    })
+}
+
+/// Moves a file, falling back to a copy when `from` and `to` are on
+/// different file systems.
+pub fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => copy_then_remove(from, to),
+        result => result,
+    }
+}
+
+/// Copies `from` next to `to`, syncs it and renames it into place before
+/// removing `from`, so a crash part way leaves at least one full copy.
+fn copy_then_remove(from: &Path, to: &Path) -> std::io::Result<()> {
+    let temp_path = temp_path_for(to);
+    let copied = std::fs::copy(from, &temp_path)
+        .and_then(|_| File::open(&temp_path)?.sync_all())
+        .and_then(|_| std::fs::rename(&temp_path, to));
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    std::fs::remove_file(from)
+}
+
+/// Runs `f` while holding exclusive access to `path`: an in-process mutex
+/// serializes threads, and an advisory lock on `<path>.lock` serializes other
+/// app instances.
+pub fn with_file_lock<T>(path: &Path, f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let lock = {
+        let mut locks = FILE_LOCKS
+            .lock()
+            .map_err(|e| format!("File lock table poisoned: {}", e))?;
+        locks.entry(path.to_path_buf()).or_default().clone()
+    };
+    // A panic while holding the lock leaves the file untouched (writes are
+    // atomic), so a poisoned mutex is safe to reuse.
+    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+
+    let lock_path = sibling_path(path, ".lock");
+    let lock_file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("Failed to open lock file {:?}: {}", lock_path, e))?;
+    lock_file
+        .lock()
+        .map_err(|e| format!("Failed to lock {:?}: {}", lock_path, e))?;
+
+    // The advisory lock is released when `lock_file` is dropped.
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Read-modify-write of a JSON list of names, the way the stores use
+    /// `with_file_lock` and `write_atomic`.
+    fn update_names(path: &Path, f: impl FnOnce(&mut Vec<String>)) {
+        with_file_lock(path, || {
+            let mut names: Vec<String> = std::fs::read_to_string(path)
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+            f(&mut names);
+            write_atomic(path, serde_json::to_string(&names).unwrap().as_bytes())
+        })
+        .unwrap();
+    }
+
+    fn read_names(path: &Path) -> Vec<String> {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn concurrent_renames_and_rescans_keep_every_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        update_names(&path, |names| {
+            *names = (0..20).map(|id| format!("session {}", id)).collect()
+        });
+
+        let mut handles = Vec::new();
+        for worker in 0..4 {
+            let path = path.clone();
+            handles.push(thread::spawn(move || {
+                for id in (worker..20).step_by(4) {
+                    update_names(&path, |names| {
+                        let name = names
+                            .iter_mut()
+                            .find(|name| **name == format!("session {}", id))
+                            .expect("session exists");
+                        *name = format!("renamed {}", id);
+                    });
+                }
+            }));
+        }
+        for worker in 0..4 {
+            let path = path.clone();
+            handles.push(thread::spawn(move || {
+                for round in 0..10 {
+                    let id = 100 + worker * 10 + round;
+                    update_names(&path, |names| {
+                        names.push(format!("session {}", id));
+                        names.sort();
+                    });
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let names = read_names(&path);
+        assert_eq!(names.len(), 60);
+        for id in 0..20 {
+            assert!(names.contains(&format!("renamed {}", id)));
+        }
+    }
+
+    #[test]
+    fn atomic_writes_leave_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|n| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    for _ in 0..20 {
+                        update_names(&path, |names| {
+                            *names = (0..n).map(|id| id.to_string()).collect()
+                        });
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        read_names(&path);
+        let mut names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["store.json", "store.json.lock"]);
+    }
+
+    #[test]
+    fn copy_then_remove_moves_the_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from.jsonl");
+        let to = dir.path().join("sub").join("to.jsonl");
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(&from, "line\n").unwrap();
+
+        copy_then_remove(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "line\n");
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("sub")).unwrap().count(),
+            1
+        );
+
+        // A failed copy keeps the original and leaves nothing behind.
+        assert!(copy_then_remove(&from, &to).is_err());
+        assert!(copy_then_remove(&to, &dir.path().join("missing/to.jsonl")).is_err());
+        assert!(to.exists());
+    }
+
+    #[test]
+    fn failed_writes_leave_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        // Renaming a file over a directory fails after the temp file is written.
+        let path = dir.path().join("taken");
+        std::fs::create_dir_all(path.join("child")).unwrap();
+        assert!(write_atomic(&path, b"{}").is_err());
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["taken"]);
+    }
+}
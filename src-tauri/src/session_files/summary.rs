@@ -0,0 +1,167 @@
+use super::events::{raw_payload, EventKind, EventParser, SessionEvent};
+use super::file::SessionInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Per-session figures computed while scanning, so the session list can be
+/// sorted and filtered without opening each rollout.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<i64>,
+    pub user_turns: u32,
+    pub tool_calls: u32,
+    pub patches_applied: u32,
+    pub total_tokens: Option<u64>,
+    pub model: Option<String>,
+    pub cli_version: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn is_patch_call(event: &SessionEvent) -> bool {
+    event.tool_name.as_deref() == Some("apply_patch")
+        || event
+            .tool_input
+            .as_deref()
+            .is_some_and(|input| input.contains("apply_patch"))
+}
+
+/// A tool result counts as successful unless its metadata reports a non-zero exit code.
+fn is_successful_result(event: &SessionEvent) -> bool {
+    let payload = raw_payload(event);
+    let exit_code = payload["output"]
+        .as_str()
+        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+        .and_then(|output| output["metadata"]["exit_code"].as_i64());
+    exit_code.unwrap_or(0) == 0
+}
+
+/// Accumulates the session id, first user message and summary figures from
+/// the events of one rollout.
+#[derive(Default)]
+struct SummaryBuilder {
+    session_id: Option<String>,
+    user_message: Option<String>,
+    summary: SessionSummary,
+    patch_calls: HashSet<String>,
+}
+
+impl SummaryBuilder {
+    fn add(&mut self, event: SessionEvent) {
+        let summary = &mut self.summary;
+        if let Some(timestamp) = parse_timestamp(event.timestamp.as_deref()) {
+            summary.started_at.get_or_insert(timestamp);
+            summary.last_event_at = Some(timestamp);
+        }
+        if event.model.is_some() {
+            summary.model = event.model.clone();
+        }
+
+        match event.kind {
+            EventKind::Meta if event.event_type == "session_meta" => {
+                let payload = raw_payload(&event);
+                self.session_id = self.session_id.take().or(event.message_id);
+                if let Some(started_at) = parse_timestamp(payload["timestamp"].as_str()) {
+                    summary.started_at = Some(started_at);
+                }
+                summary.cli_version = payload["cli_version"].as_str().map(str::to_string);
+                summary.git_branch = payload["git"]["branch"].as_str().map(str::to_string);
+            }
+            EventKind::Meta if event.event_type == "token_count" => {
+                let payload = raw_payload(&event);
+                if let Some(total) = payload["info"]["total_token_usage"]["total_tokens"].as_u64() {
+                    summary.total_tokens = Some(total);
+                }
+            }
+            EventKind::User => {
+                summary.user_turns += 1;
+                if self.user_message.is_none() {
+                    self.user_message = event.text.filter(|text| !text.is_empty());
+                }
+            }
+            EventKind::ToolCall => {
+                summary.tool_calls += 1;
+                if is_patch_call(&event) {
+                    if let Some(call_id) = event.message_id {
+                        self.patch_calls.insert(call_id);
+                    }
+                }
+            }
+            EventKind::ToolResult => {
+                let is_patch = event
+                    .parent_id
+                    .as_ref()
+                    .is_some_and(|id| self.patch_calls.contains(id));
+                if is_patch && is_successful_result(&event) {
+                    summary.patches_applied += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `None` if no `session_meta` record carried an id.
+    fn finish(self) -> Option<(SessionInfo, SessionSummary)> {
+        let mut summary = self.summary;
+        if let (Some(start), Some(end)) = (summary.started_at, summary.last_event_at) {
+            summary.duration_seconds = Some((end - start).num_seconds().max(0));
+        }
+        Some((
+            SessionInfo {
+                session_id: self.session_id?,
+                user_message: self.user_message,
+            },
+            summary,
+        ))
+    }
+}
+
+/// Everything the session catalog needs from a rollout, read in one pass.
+pub struct RolloutSummary {
+    pub line_count: usize,
+    /// The first record, which holds the `session_meta` (cwd, git remote, ...).
+    pub first_line: Option<Value>,
+    /// The session id, first user message and figures; `None` without a session id.
+    pub session: Option<(SessionInfo, SessionSummary)>,
+}
+
+/// Streams a rollout once, counting its lines and extracting the first
+/// record, the session id, the first user message and the summary figures.
+pub fn summarize_session<P: AsRef<Path>>(file_path: P) -> Result<RolloutSummary, String> {
+    let file = File::open(&file_path)
+        .map_err(|e| format!("Failed to open file {:?}: {}", file_path.as_ref(), e))?;
+    let mut line_count = 0;
+    let mut first_line = None;
+    let mut parser = EventParser::new();
+    let mut builder = SummaryBuilder::default();
+
+    // Like `open_session_events`, stop at the first unreadable line.
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if line_count == 0 {
+            first_line = serde_json::from_str(&line).ok();
+        }
+        line_count += 1;
+        if let Some(event) = parser.parse_line(&line) {
+            builder.add(event);
+        }
+    }
+
+    Ok(RolloutSummary {
+        line_count,
+        first_line,
+        session: builder.finish(),
+    })
+}
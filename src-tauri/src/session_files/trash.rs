@@ -0,0 +1,455 @@
+use super::file::get_sessions_path;
+use super::get::get_app_data_dir;
+use super::save::sync_session_files;
+use super::store::{move_file, with_file_lock, write_atomic};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+const TRASH_DIR_NAME: &str = "trash";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub const TRASH_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// File name inside the trash directory.
+    pub id: String,
+    /// Shared by every file deleted by the same command, for `undo_delete`.
+    pub batch_id: String,
+    pub original_path: String,
+    pub project_path: String,
+    pub size_bytes: u64,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashManifest {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub entries: Vec<TrashEntry>,
+}
+
+impl Default for TrashManifest {
+    fn default() -> Self {
+        TrashManifest {
+            schema_version: TRASH_SCHEMA_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Result for one file of a batch operation. Exactly one of `entry` and
+/// `error` is set.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashOutcome {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<TrashEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TrashOutcome {
+    fn ok(path: String, entry: TrashEntry) -> Self {
+        TrashOutcome {
+            path,
+            entry: Some(entry),
+            error: None,
+        }
+    }
+
+    fn failed(path: String, error: String) -> Self {
+        TrashOutcome {
+            path,
+            entry: None,
+            error: Some(error),
+        }
+    }
+}
+
+pub fn get_trash_dir() -> Result<PathBuf, String> {
+    let trash_dir = get_app_data_dir()?.join(TRASH_DIR_NAME);
+    std::fs::create_dir_all(&trash_dir)
+        .map_err(|e| format!("Failed to create trash dir: {}", e))?;
+    Ok(trash_dir)
+}
+
+fn read_manifest(manifest_path: &Path) -> Result<TrashManifest, String> {
+    if !manifest_path.exists() {
+        return Ok(TrashManifest::default());
+    }
+    let manifest_str = read_to_string(manifest_path)
+        .map_err(|e| format!("Failed to read trash manifest: {}", e))?;
+    let manifest: TrashManifest = serde_json::from_str(&manifest_str)
+        .map_err(|e| format!("Failed to parse trash manifest: {}", e))?;
+    if manifest.schema_version > TRASH_SCHEMA_VERSION {
+        return Err(format!(
+            "Trash manifest version {} is newer than supported version {}",
+            manifest.schema_version, TRASH_SCHEMA_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+pub fn load_trash_manifest() -> Result<TrashManifest, String> {
+    read_manifest(&get_trash_dir()?.join(MANIFEST_FILE_NAME))
+}
+
+/// Read-modify-write of the manifest in `trash_dir` under its lock. File
+/// moves happen inside `f`, so the manifest and the trash directory change
+/// together.
+fn update_manifest<T>(
+    trash_dir: &Path,
+    f: impl FnOnce(&mut TrashManifest) -> Result<T, String>,
+) -> Result<T, String> {
+    let manifest_path = trash_dir.join(MANIFEST_FILE_NAME);
+    with_file_lock(&manifest_path, || {
+        let mut manifest = read_manifest(&manifest_path)?;
+        let result = f(&mut manifest);
+        // Write even if `f` failed part way, so moves it already made are recorded.
+        let json_str = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize trash manifest: {}", e))?;
+        write_atomic(&manifest_path, json_str.as_bytes())?;
+        result
+    })
+}
+
+/// Tells the session catalog about the files of the successful outcomes.
+fn sync_outcomes(outcomes: &[TrashOutcome]) {
+    let changed: Vec<&str> = outcomes
+        .iter()
+        .filter_map(|outcome| outcome.entry.as_ref())
+        .map(|entry| entry.original_path.as_str())
+        .collect();
+    if !changed.is_empty() {
+        // The files have moved either way; the watcher catches up otherwise.
+        if let Err(e) = sync_session_files(&changed) {
+            eprintln!("Failed to update session catalog: {}", e);
+        }
+    }
+}
+
+/// Moves rollouts inside `sessions_dir` (already canonical) to `trash_dir`
+/// as one batch.
+pub fn trash_files_in(
+    trash_dir: &Path,
+    sessions_dir: &Path,
+    project_path: &str,
+    session_paths: &[String],
+) -> Result<Vec<TrashOutcome>, String> {
+    let deleted_at = Utc::now();
+    let batch_id = deleted_at.format("%Y%m%dT%H%M%S%.9f").to_string();
+
+    update_manifest(trash_dir, |manifest| {
+        let mut outcomes = Vec::new();
+        for session_path in session_paths {
+            let path = Path::new(session_path);
+            // Resolved first, so `..` and symlinks cannot reach outside.
+            let inside = std::fs::canonicalize(path)
+                .is_ok_and(|resolved| resolved.starts_with(sessions_dir));
+            if !inside {
+                outcomes.push(TrashOutcome::failed(
+                    session_path.clone(),
+                    format!("{} is not inside the sessions directory", session_path),
+                ));
+                continue;
+            }
+
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let id = format!("{}-{}", batch_id, file_name);
+            let size_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            if let Err(e) = move_file(path, &trash_dir.join(&id)) {
+                outcomes.push(TrashOutcome::failed(
+                    session_path.clone(),
+                    format!("Failed to delete session {}: {}", session_path, e),
+                ));
+                continue;
+            }
+
+            let entry = TrashEntry {
+                id,
+                batch_id: batch_id.clone(),
+                original_path: session_path.clone(),
+                project_path: project_path.to_string(),
+                size_bytes,
+                deleted_at,
+            };
+            manifest.entries.push(entry.clone());
+            outcomes.push(TrashOutcome::ok(session_path.clone(), entry));
+        }
+        Ok(outcomes)
+    })
+}
+
+/// Moves rollouts of one project to the trash and drops them from the
+/// session catalog. Every file is attempted; failures are reported per file.
+pub fn move_to_trash(
+    project_path: &str,
+    session_paths: &[String],
+) -> Result<Vec<TrashOutcome>, String> {
+    let sessions_dir = std::fs::canonicalize(get_sessions_path()?)
+        .map_err(|e| format!("Failed to resolve sessions dir: {}", e))?;
+    let outcomes = trash_files_in(
+        &get_trash_dir()?,
+        &sessions_dir,
+        project_path,
+        session_paths,
+    )?;
+    sync_outcomes(&outcomes);
+    Ok(outcomes)
+}
+
+/// Moves files in `trash_dir` back to their original paths. A file whose
+/// original path has been reused is not restored.
+pub fn restore_in(trash_dir: &Path, ids: &[String]) -> Result<Vec<TrashOutcome>, String> {
+    update_manifest(trash_dir, |manifest| {
+        let mut outcomes = Vec::new();
+        for id in ids {
+            let Some(index) = manifest.entries.iter().position(|entry| &entry.id == id) else {
+                outcomes.push(TrashOutcome::failed(
+                    id.clone(),
+                    format!("No trashed session with id {}", id),
+                ));
+                continue;
+            };
+            let entry = &manifest.entries[index];
+            let original_path = PathBuf::from(&entry.original_path);
+            if original_path.exists() {
+                outcomes.push(TrashOutcome::failed(
+                    entry.original_path.clone(),
+                    format!("{:?} already exists", original_path),
+                ));
+                continue;
+            }
+
+            let result = original_path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| move_file(&trash_dir.join(id), &original_path));
+            match result {
+                Ok(()) => {
+                    let entry = manifest.entries.remove(index);
+                    outcomes.push(TrashOutcome::ok(entry.original_path.clone(), entry));
+                }
+                Err(e) => outcomes.push(TrashOutcome::failed(
+                    entry.original_path.clone(),
+                    format!("Failed to restore {}: {}", id, e),
+                )),
+            }
+        }
+        Ok(outcomes)
+    })
+}
+
+/// Moves trashed files back to their original paths and adds them back to
+/// the session catalog. A file whose original path has been reused is not
+/// restored.
+pub fn restore_from_trash(ids: &[String]) -> Result<Vec<TrashOutcome>, String> {
+    let outcomes = restore_in(&get_trash_dir()?, ids)?;
+    sync_outcomes(&outcomes);
+    Ok(outcomes)
+}
+
+/// Ids of every file of the most recent delete in `manifest`.
+fn latest_batch(manifest: &TrashManifest) -> Vec<String> {
+    let Some(latest) = manifest.entries.iter().max_by_key(|entry| entry.deleted_at) else {
+        return Vec::new();
+    };
+    manifest
+        .entries
+        .iter()
+        .filter(|entry| entry.batch_id == latest.batch_id)
+        .map(|entry| entry.id.clone())
+        .collect()
+}
+
+#[tauri::command]
+pub async fn list_trash() -> Result<Vec<TrashEntry>, String> {
+    let mut entries = load_trash_manifest()?.entries;
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn restore_sessions(trash_ids: Vec<String>) -> Result<Vec<TrashOutcome>, String> {
+    restore_from_trash(&trash_ids)
+}
+
+/// Restores every file of the most recent delete.
+#[tauri::command]
+pub async fn undo_delete() -> Result<Vec<TrashOutcome>, String> {
+    restore_from_trash(&latest_batch(&load_trash_manifest()?))
+}
+
+/// Permanently removes everything in the trash and returns how many files were removed.
+#[tauri::command]
+pub async fn empty_trash() -> Result<usize, String> {
+    let trash_dir = get_trash_dir()?;
+    update_manifest(&trash_dir, |manifest| {
+        let mut kept = Vec::new();
+        let mut removed = 0;
+        for entry in manifest.entries.drain(..) {
+            match std::fs::remove_file(trash_dir.join(&entry.id)) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    eprintln!("Failed to remove trashed file {}: {}", entry.id, e);
+                    kept.push(entry);
+                }
+            }
+        }
+        manifest.entries = kept;
+        Ok(removed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Dirs {
+        _root: tempfile::TempDir,
+        sessions: PathBuf,
+        trash: PathBuf,
+    }
+
+    fn dirs() -> Dirs {
+        let root = tempfile::tempdir().unwrap();
+        let root_path = std::fs::canonicalize(root.path()).unwrap();
+        let sessions = root_path.join("sessions/2025/09/20");
+        let trash = root_path.join("trash");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::create_dir_all(&trash).unwrap();
+        Dirs {
+            _root: root,
+            sessions: root_path.join("sessions"),
+            trash,
+        }
+    }
+
+    fn rollout(dirs: &Dirs, name: &str) -> String {
+        let path = dirs.sessions.join("2025/09/20").join(name);
+        std::fs::write(&path, name).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn trash(dirs: &Dirs, paths: &[String]) -> Vec<TrashOutcome> {
+        trash_files_in(&dirs.trash, &dirs.sessions, "/home/me/app", paths).unwrap()
+    }
+
+    fn manifest(dirs: &Dirs) -> TrashManifest {
+        read_manifest(&dirs.trash.join(MANIFEST_FILE_NAME)).unwrap()
+    }
+
+    #[test]
+    fn manifest_round_trips_and_rejects_newer_versions() {
+        let dirs = dirs();
+        let a = rollout(&dirs, "a.jsonl");
+        let entry = trash(&dirs, std::slice::from_ref(&a))[0]
+            .entry
+            .clone()
+            .unwrap();
+
+        let manifest = manifest(&dirs);
+        assert_eq!(manifest.schema_version, TRASH_SCHEMA_VERSION);
+        assert_eq!(manifest.entries.len(), 1);
+        let read = &manifest.entries[0];
+        assert_eq!(read.id, entry.id);
+        assert_eq!(read.batch_id, entry.batch_id);
+        assert_eq!(read.original_path, a);
+        assert_eq!(read.project_path, "/home/me/app");
+        assert_eq!(read.size_bytes, "a.jsonl".len() as u64);
+        assert_eq!(read.deleted_at, entry.deleted_at);
+
+        let newer = TrashManifest {
+            schema_version: TRASH_SCHEMA_VERSION + 1,
+            ..manifest
+        };
+        let manifest_path = dirs.trash.join(MANIFEST_FILE_NAME);
+        std::fs::write(&manifest_path, serde_json::to_string(&newer).unwrap()).unwrap();
+        assert!(read_manifest(&manifest_path).unwrap_err().contains("newer"));
+    }
+
+    #[test]
+    fn batch_outcomes_are_reported_per_file() {
+        let dirs = dirs();
+        let a = rollout(&dirs, "a.jsonl");
+        let missing = dirs
+            .sessions
+            .join("missing.jsonl")
+            .to_string_lossy()
+            .to_string();
+        let outside = dirs.trash.join("../outside.jsonl");
+        std::fs::write(&outside, "").unwrap();
+        let outside = outside.to_string_lossy().to_string();
+
+        let outcomes = trash(&dirs, &[a.clone(), missing, outside.clone()]);
+        assert!(outcomes[0].entry.is_some() && outcomes[0].error.is_none());
+        assert!(outcomes[1].error.as_ref().unwrap().contains("not inside"));
+        assert!(outcomes[2].error.as_ref().unwrap().contains("not inside"));
+        assert!(!Path::new(&a).exists());
+        assert!(Path::new(&outside).exists());
+        let id = &outcomes[0].entry.as_ref().unwrap().id;
+        assert!(dirs.trash.join(id).exists());
+        assert_eq!(manifest(&dirs).entries.len(), 1);
+    }
+
+    #[test]
+    fn restore_puts_files_back_unless_their_path_was_reused() {
+        let dirs = dirs();
+        let a = rollout(&dirs, "a.jsonl");
+        let b = rollout(&dirs, "b.jsonl");
+        let ids: Vec<String> = trash(&dirs, &[a.clone(), b.clone()])
+            .into_iter()
+            .map(|outcome| outcome.entry.unwrap().id)
+            .collect();
+        std::fs::write(&b, "reused").unwrap();
+
+        let request = vec![ids[0].clone(), ids[1].clone(), "missing".to_string()];
+        let outcomes = restore_in(&dirs.trash, &request).unwrap();
+        assert_eq!(outcomes[0].path, a);
+        assert!(outcomes[0].entry.is_some());
+        assert!(outcomes[1]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("already exists"));
+        assert!(outcomes[2]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("No trashed session"));
+
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "a.jsonl");
+        assert_eq!(std::fs::read_to_string(&b).unwrap(), "reused");
+        let manifest = manifest(&dirs);
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.entries[0].id, ids[1]);
+    }
+
+    #[test]
+    fn undo_restores_only_the_latest_batch() {
+        let dirs = dirs();
+        let a = rollout(&dirs, "a.jsonl");
+        let b = rollout(&dirs, "b.jsonl");
+        let c = rollout(&dirs, "c.jsonl");
+        trash(&dirs, std::slice::from_ref(&a));
+        trash(&dirs, &[b.clone(), c.clone()]);
+
+        let ids = latest_batch(&manifest(&dirs));
+        assert_eq!(ids.len(), 2);
+        let outcomes = restore_in(&dirs.trash, &ids).unwrap();
+        assert!(outcomes.iter().all(|outcome| outcome.entry.is_some()));
+        assert!(!Path::new(&a).exists());
+        assert!(Path::new(&b).exists() && Path::new(&c).exists());
+
+        let ids = latest_batch(&manifest(&dirs));
+        restore_in(&dirs.trash, &ids).unwrap();
+        assert!(Path::new(&a).exists());
+        assert!(latest_batch(&manifest(&dirs)).is_empty());
+    }
+}
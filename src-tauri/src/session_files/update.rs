@@ -1,35 +1,63 @@
-use super::get::get_cache_path_for_project;
-use serde_json::{json, Value};
-use std::fs::read_to_string;
+use super::metadata::{update_metadata, MetadataStore};
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+pub(super) fn set_title(store: &mut MetadataStore, conversation_id: String, title: String) {
+    store.sessions.entry(conversation_id).or_default().title = non_empty(title);
+}
 
 #[tauri::command]
-pub async fn update_cache_title(
-    project_path: String,
-    session_path: String,
-    preview: String,
-) -> Result<(), String> {
-    let cache_path = get_cache_path_for_project(&project_path)?;
-    if cache_path.exists() {
-        let mut cache_json: Value = serde_json::from_str(
-            &read_to_string(&cache_path).map_err(|e| format!("Failed to read cache: {}", e))?,
-        )
-        .map_err(|e| format!("Failed to parse cache: {}", e))?;
+pub async fn update_session_title(conversation_id: String, title: String) -> Result<(), String> {
+    update_metadata(|store| {
+        set_title(store, conversation_id, title);
+        Ok(())
+    })
+}
 
-        if let Some(arr) = cache_json["sessions"].as_array_mut() {
-            for item in arr.iter_mut() {
-                if item["path"].as_str() == Some(&session_path) {
-                    let truncated_text: String = preview.chars().take(50).collect();
-                    item["preview"] = json!(truncated_text);
-                    break;
-                }
-            }
+#[tauri::command]
+pub async fn add_session_tag(conversation_id: String, tag: String) -> Result<Vec<String>, String> {
+    let tag = non_empty(tag).ok_or("Tag cannot be empty")?;
+    update_metadata(|store| {
+        let metadata = store.sessions.entry(conversation_id).or_default();
+        if !metadata.tags.contains(&tag) {
+            metadata.tags.push(tag);
         }
+        Ok(metadata.tags.clone())
+    })
+}
 
-        let new_str = serde_json::to_string_pretty(&cache_json)
-            .map_err(|e| format!("Failed to serialize updated cache: {}", e))?;
-        std::fs::write(&cache_path, new_str)
-            .map_err(|e| format!("Failed to update cache: {}", e))?;
-    }
+#[tauri::command]
+pub async fn remove_session_tag(
+    conversation_id: String,
+    tag: String,
+) -> Result<Vec<String>, String> {
+    update_metadata(|store| {
+        let metadata = store.sessions.entry(conversation_id).or_default();
+        metadata.tags.retain(|t| t != tag.trim());
+        Ok(metadata.tags.clone())
+    })
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn toggle_session_star(conversation_id: String) -> Result<bool, String> {
+    update_metadata(|store| {
+        let metadata = store.sessions.entry(conversation_id).or_default();
+        metadata.starred = !metadata.starred;
+        Ok(metadata.starred)
+    })
+}
+
+#[tauri::command]
+pub async fn set_session_notes(conversation_id: String, notes: String) -> Result<(), String> {
+    update_metadata(|store| {
+        store.sessions.entry(conversation_id).or_default().notes = non_empty(notes);
+        Ok(())
+    })
 }
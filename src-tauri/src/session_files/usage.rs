@@ -0,0 +1,460 @@
+use super::events::{open_session_events, EventKind};
+use super::file::{get_sessions_path, read_session_cwd};
+use super::pricing::{load_price_table, PriceTable};
+use super::scan::scan_jsonl_files;
+use super::utils::extract_datetime;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::AddAssign;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Reads a `token_count` usage object, which uses snake_case keys.
+    pub fn from_value(value: &Value) -> Self {
+        let get = |key: &str| value[key].as_u64().unwrap_or(0);
+        TokenUsage {
+            input_tokens: get("input_tokens"),
+            cached_input_tokens: get("cached_input_tokens"),
+            output_tokens: get("output_tokens"),
+            reasoning_output_tokens: get("reasoning_output_tokens"),
+            total_tokens: get("total_tokens"),
+        }
+    }
+
+    fn saturating_sub(&self, other: &TokenUsage) -> Self {
+        TokenUsage {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            cached_input_tokens: self
+                .cached_input_tokens
+                .saturating_sub(other.cached_input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            reasoning_output_tokens: self
+                .reasoning_output_tokens
+                .saturating_sub(other.reasoning_output_tokens),
+            total_tokens: self.total_tokens.saturating_sub(other.total_tokens),
+        }
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_output_tokens += other.reasoning_output_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnUsage {
+    pub timestamp: Option<String>,
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    /// `None` when the model has no entry in the price table.
+    pub cost: Option<f64>,
+}
+
+/// Usage totals with an estimated cost. `unpriced_tokens` counts tokens of
+/// models missing from the price table, which are not part of `cost`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotals {
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    pub cost: f64,
+    pub unpriced_tokens: u64,
+}
+
+impl UsageTotals {
+    /// Totals of a session, with every turn priced at the model it ran on.
+    pub fn from_turns(turns: &[TurnUsage]) -> Self {
+        let mut totals = UsageTotals::default();
+        for turn in turns {
+            totals.add_turn(turn);
+        }
+        totals
+    }
+
+    fn add_turn(&mut self, turn: &TurnUsage) {
+        self.usage += turn.usage;
+        match turn.cost {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced_tokens += turn.usage.total_tokens,
+        }
+    }
+
+    fn add(&mut self, other: &UsageTotals) {
+        self.usage += other.usage;
+        self.cost += other.cost;
+        self.unpriced_tokens += other.unpriced_tokens;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUsage {
+    pub path: String,
+    pub conversation_id: String,
+    pub cwd: Option<String>,
+    pub day: Option<NaiveDate>,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectUsage {
+    pub cwd: String,
+    pub sessions: usize,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    pub day: NaiveDate,
+    pub sessions: usize,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub total: UsageTotals,
+    pub sessions: Vec<SessionUsage>,
+    pub projects: Vec<ProjectUsage>,
+    /// One entry per calendar day with activity, oldest first. Each turn
+    /// counts towards the day it ran on.
+    pub days: Vec<DailyUsage>,
+}
+
+/// Per-turn usage of a rollout. `token_count` events carry a running total,
+/// so each turn is the difference to the previous total; repeated events
+/// with an unchanged total contribute nothing. Events without a total fall
+/// back to their `last_token_usage`.
+pub fn read_turn_usage<P: AsRef<Path>>(
+    file_path: P,
+    prices: &PriceTable,
+) -> Result<(String, Vec<TurnUsage>), String> {
+    let mut conversation_id = String::new();
+    let mut previous = TokenUsage::default();
+    let mut turns = Vec::new();
+
+    for event in open_session_events(file_path)?.map_while(Result::ok) {
+        if event.kind != EventKind::Meta {
+            continue;
+        }
+        if event.event_type == "session_meta" {
+            conversation_id = event.message_id.clone().unwrap_or_default();
+            continue;
+        }
+        if event.event_type != "token_count" {
+            continue;
+        }
+
+        let value: Value = serde_json::from_str(&event.raw_json).unwrap_or(Value::Null);
+        let info = &value["payload"]["info"];
+        let usage = if !info["total_token_usage"].is_null() {
+            let total = TokenUsage::from_value(&info["total_token_usage"]);
+            if total.total_tokens <= previous.total_tokens {
+                continue;
+            }
+            let usage = total.saturating_sub(&previous);
+            previous = total;
+            usage
+        } else if !info["last_token_usage"].is_null() {
+            let usage = TokenUsage::from_value(&info["last_token_usage"]);
+            previous += usage;
+            usage
+        } else {
+            continue;
+        };
+        if usage.total_tokens == 0 {
+            continue;
+        }
+        turns.push(TurnUsage {
+            cost: prices.cost(event.model.as_deref(), &usage),
+            timestamp: event.timestamp,
+            model: event.model,
+            usage,
+        });
+    }
+
+    Ok((conversation_id, turns))
+}
+
+/// Local calendar day of a turn, like the `YYYY/MM/DD` directories Codex writes.
+fn turn_day(turn: &TurnUsage) -> Option<NaiveDate> {
+    turn.timestamp
+        .as_deref()
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| dt.with_timezone(&Local).date_naive())
+}
+
+fn session_day(path: &str, turns: &[TurnUsage]) -> Option<NaiveDate> {
+    extract_datetime(path)
+        .map(|dt| dt.date())
+        .or_else(|| turns.first().and_then(turn_day))
+}
+
+/// A session's usage split by the day each turn ran on, so a session that
+/// spans midnight counts towards both days. Turns without a timestamp go to
+/// `fallback`.
+fn usage_by_day(
+    turns: &[TurnUsage],
+    fallback: Option<NaiveDate>,
+) -> BTreeMap<NaiveDate, UsageTotals> {
+    let mut days: BTreeMap<NaiveDate, UsageTotals> = BTreeMap::new();
+    for turn in turns {
+        if let Some(day) = turn_day(turn).or(fallback) {
+            days.entry(day).or_default().add_turn(turn);
+        }
+    }
+    days
+}
+
+#[tauri::command]
+pub async fn get_session_usage(session_path: String) -> Result<Vec<TurnUsage>, String> {
+    let prices = load_price_table()?;
+    Ok(read_turn_usage(&session_path, &prices)?.1)
+}
+
+#[tauri::command]
+pub async fn get_token_usage(project_path: Option<String>) -> Result<UsageReport, String> {
+    let prices = load_price_table()?;
+    let sessions_dir = get_sessions_path()?;
+    let mut total = UsageTotals::default();
+    let mut sessions = Vec::new();
+    let mut projects: HashMap<String, ProjectUsage> = HashMap::new();
+    let mut days: BTreeMap<NaiveDate, DailyUsage> = BTreeMap::new();
+
+    for entry in scan_jsonl_files(&sessions_dir) {
+        let path = entry.path();
+        let cwd = read_session_cwd(path);
+        if project_path.is_some() && cwd != project_path {
+            continue;
+        }
+
+        let (conversation_id, turns) = match read_turn_usage(path, &prices) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Failed to read token usage from {:?}: {}", path, e);
+                continue;
+            }
+        };
+        if turns.is_empty() {
+            continue;
+        }
+
+        let file_path = path.to_string_lossy().to_string();
+        let totals = UsageTotals::from_turns(&turns);
+        total.add(&totals);
+
+        if let Some(cwd) = &cwd {
+            let project = projects.entry(cwd.clone()).or_insert_with(|| ProjectUsage {
+                cwd: cwd.clone(),
+                sessions: 0,
+                totals: UsageTotals::default(),
+            });
+            project.sessions += 1;
+            project.totals.add(&totals);
+        }
+
+        let day = session_day(&file_path, &turns);
+        for (turn_day, turn_totals) in usage_by_day(&turns, day) {
+            let daily = days.entry(turn_day).or_insert_with(|| DailyUsage {
+                day: turn_day,
+                sessions: 0,
+                totals: UsageTotals::default(),
+            });
+            daily.sessions += 1;
+            daily.totals.add(&turn_totals);
+        }
+
+        sessions.push(SessionUsage {
+            path: file_path,
+            conversation_id,
+            cwd,
+            day,
+            totals,
+        });
+    }
+
+    let mut projects: Vec<ProjectUsage> = projects.into_values().collect();
+    projects.sort_by(|a, b| {
+        b.totals
+            .usage
+            .total_tokens
+            .cmp(&a.totals.usage.total_tokens)
+    });
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.day));
+
+    Ok(UsageReport {
+        total,
+        sessions,
+        projects,
+        days: days.into_values().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usage(total: u64) -> Value {
+        json!({
+            "input_tokens": total / 2,
+            "cached_input_tokens": 0,
+            "output_tokens": total / 2,
+            "reasoning_output_tokens": 0,
+            "total_tokens": total,
+        })
+    }
+
+    fn token_count(timestamp: &str, info: Value) -> String {
+        json!({
+            "timestamp": timestamp,
+            "type": "event_msg",
+            "payload": { "type": "token_count", "info": info },
+        })
+        .to_string()
+    }
+
+    fn turns(lines: &[String]) -> Vec<TurnUsage> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let meta = json!({
+            "timestamp": "2025-09-20T10:00:00.000Z",
+            "type": "session_meta",
+            "payload": { "id": "0199-abc" },
+        });
+        let context = json!({
+            "timestamp": "2025-09-20T10:00:00.000Z",
+            "type": "turn_context",
+            "payload": { "model": "gpt-5" },
+        });
+        let mut contents = format!("{}\n{}\n", meta, context);
+        for line in lines {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        std::fs::write(&path, contents).unwrap();
+        let (conversation_id, turns) = read_turn_usage(&path, &PriceTable::default()).unwrap();
+        assert_eq!(conversation_id, "0199-abc");
+        turns
+    }
+
+    fn totals(turns: &[TurnUsage]) -> Vec<u64> {
+        turns.iter().map(|turn| turn.usage.total_tokens).collect()
+    }
+
+    #[test]
+    fn running_totals_become_per_turn_deltas() {
+        let turns = turns(&[
+            token_count(
+                "2025-09-20T10:00:01Z",
+                json!({ "total_token_usage": usage(100), "last_token_usage": usage(100) }),
+            ),
+            // The last usage disagrees; the running total wins.
+            token_count(
+                "2025-09-20T10:00:02Z",
+                json!({ "total_token_usage": usage(250), "last_token_usage": usage(999) }),
+            ),
+        ]);
+        assert_eq!(totals(&turns), vec![100, 150]);
+        assert_eq!(turns[1].usage.input_tokens, 75);
+        assert_eq!(turns[1].model.as_deref(), Some("gpt-5"));
+        assert!(turns[1].cost.is_some());
+    }
+
+    #[test]
+    fn repeated_token_counts_add_nothing() {
+        let turns = turns(&[
+            token_count(
+                "2025-09-20T10:00:01Z",
+                json!({ "total_token_usage": usage(100) }),
+            ),
+            token_count(
+                "2025-09-20T10:00:02Z",
+                json!({ "total_token_usage": usage(100) }),
+            ),
+            token_count("2025-09-20T10:00:03Z", Value::Null),
+            token_count(
+                "2025-09-20T10:00:04Z",
+                json!({ "total_token_usage": usage(180) }),
+            ),
+        ]);
+        assert_eq!(totals(&turns), vec![100, 80]);
+    }
+
+    #[test]
+    fn last_token_usage_is_used_without_a_total() {
+        let turns = turns(&[
+            token_count(
+                "2025-09-20T10:00:01Z",
+                json!({ "last_token_usage": usage(40) }),
+            ),
+            token_count(
+                "2025-09-20T10:00:02Z",
+                json!({ "last_token_usage": usage(60) }),
+            ),
+            // A total after that continues from what was counted so far.
+            token_count(
+                "2025-09-20T10:00:03Z",
+                json!({ "total_token_usage": usage(130) }),
+            ),
+        ]);
+        assert_eq!(totals(&turns), vec![40, 60, 30]);
+    }
+
+    #[test]
+    fn turns_are_bucketed_by_their_own_day() {
+        let turns = turns(&[
+            token_count(
+                "2025-09-20T10:00:00Z",
+                json!({ "total_token_usage": usage(100) }),
+            ),
+            token_count(
+                "2025-09-22T10:00:00Z",
+                json!({ "total_token_usage": usage(300) }),
+            ),
+        ]);
+        let fallback = NaiveDate::from_ymd_opt(2025, 9, 1);
+        let days = usage_by_day(&turns, fallback);
+        let by_day: Vec<(NaiveDate, u64)> = days
+            .iter()
+            .map(|(day, totals)| (*day, totals.usage.total_tokens))
+            .collect();
+        let day = |ts: &str| {
+            DateTime::parse_from_rfc3339(ts)
+                .unwrap()
+                .with_timezone(&Local)
+                .date_naive()
+        };
+        assert_eq!(
+            by_day,
+            vec![
+                (day("2025-09-20T10:00:00Z"), 100),
+                (day("2025-09-22T10:00:00Z"), 200)
+            ]
+        );
+    }
+}
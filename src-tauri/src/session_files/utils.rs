@@ -1,13 +1,4 @@
 use chrono::NaiveDateTime;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
-use std::path::PathBuf;
-
-pub fn count_lines(file_path: &PathBuf) -> io::Result<usize> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    Ok(reader.lines().count())
-}
 
 pub fn extract_datetime(path_str: &str) -> Option<NaiveDateTime> {
     let parts: Vec<&str> = path_str.split('/').collect();
@@ -21,8 +12,20 @@ pub fn extract_datetime(path_str: &str) -> Option<NaiveDateTime> {
     let time_part = filename
         .split('T')
         .nth(1)
-        .and_then(|s| Some(s.split('-').take(3).collect::<Vec<_>>().join("-")))
+        .map(|s| s.split('-').take(3).collect::<Vec<_>>().join("-"))
         .unwrap_or_default();
     let datetime_str = format!("{}-{}-{}T{}", year, month, day, time_part);
     NaiveDateTime::parse_from_str(&datetime_str, "%Y-%m-%dT%H-%M-%S").ok()
 }
+
+/// Identifies a file across renames (its inode), where the platform has one.
+#[cfg(unix)]
+pub fn file_id(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+pub fn file_id(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
@@ -0,0 +1,315 @@
+use super::cache::CachedSession;
+use super::catalog::{
+    catalog_paths_under, invalidate_catalog, refresh_catalog, save_catalog, update_catalog_paths,
+    CatalogChange, CatalogUpdate,
+};
+use super::file::get_sessions_path;
+use super::index::remove_from_index;
+use super::scan::scan_jsonl_files;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const SESSION_ADDED_EVENT: &str = "session-added";
+pub const SESSION_UPDATED_EVENT: &str = "session-updated";
+pub const SESSION_REMOVED_EVENT: &str = "session-removed";
+pub const SESSION_RENAMED_EVENT: &str = "session-renamed";
+
+/// Codex appends to a rollout many times a second while a turn runs, so a
+/// file is read again only once it has been quiet for this long...
+const QUIET_PERIOD: Duration = Duration::from_secs(2);
+/// ...or has been changing for this long, so a running session still shows up.
+const MAX_DELAY: Duration = Duration::from_secs(15);
+/// The catalog on disk is written once changes stop for this long...
+const SAVE_DELAY: Duration = Duration::from_secs(30);
+/// ...or at least this often while they keep coming.
+const SAVE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Kept for the lifetime of the app; dropping the watcher stops it.
+static WATCHER: LazyLock<Mutex<Option<RecommendedWatcher>>> = LazyLock::new(|| Mutex::new(None));
+/// Where session change events go, also for changes the app makes itself.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Payload of `session-added`, `session-updated`, `session-renamed` and
+/// `session-removed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionChangeEvent {
+    pub path: String,
+    /// Where the file was before, for `session-renamed`.
+    pub previous_path: Option<String>,
+    pub project_path: Option<String>,
+    /// Not set for `session-removed`.
+    pub session: Option<CachedSession>,
+}
+
+fn is_rollout(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+}
+
+/// Rollouts an event refers to. A new day directory is watched only after it
+/// appears, so files Codex wrote into it right away are picked up from it.
+/// Anything else that no longer exists may be a directory that was removed
+/// or moved away, and is passed on for `expand_removed`.
+fn changed_rollouts(event: notify::Event) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for path in event.paths {
+        if path.is_dir() {
+            paths.extend(scan_jsonl_files(&path).map(|entry| entry.into_path()));
+        } else if is_rollout(&path) || !path.exists() {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Tells the frontend about catalog updates, once there is an app to tell,
+/// and drops removed sessions from the search index.
+pub fn publish(updates: Vec<CatalogUpdate>) {
+    let removed: Vec<String> = updates
+        .iter()
+        .filter_map(|update| match update.change {
+            CatalogChange::Removed => Some(update.path.clone()),
+            CatalogChange::Renamed => update.previous_path.clone(),
+            _ => None,
+        })
+        .collect();
+    if !removed.is_empty() {
+        if let Err(e) = remove_from_index(&removed) {
+            eprintln!("Failed to update search index: {}", e);
+        }
+    }
+
+    let Some(app) = APP.get() else {
+        return;
+    };
+    for update in updates {
+        let entry = update.entry.as_ref();
+        let cwd = entry.and_then(|entry| entry.cwd.clone());
+        let session = entry.and_then(|entry| entry.session.clone());
+
+        let event = match update.change {
+            CatalogChange::Added => SESSION_ADDED_EVENT,
+            CatalogChange::Updated => SESSION_UPDATED_EVENT,
+            CatalogChange::Renamed => SESSION_RENAMED_EVENT,
+            CatalogChange::Removed => SESSION_REMOVED_EVENT,
+        };
+        let payload = SessionChangeEvent {
+            path: update.path,
+            previous_path: update.previous_path,
+            project_path: cwd.or(update.previous_cwd),
+            session,
+        };
+        if let Err(e) = app.emit(event, payload) {
+            eprintln!("Failed to emit {}: {}", event, e);
+        }
+    }
+}
+
+/// Replaces paths that are gone and are not rollouts, i.e. removed
+/// directories, with the catalogued rollouts that were under them.
+fn expand_removed(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut expanded = BTreeSet::new();
+    for path in paths {
+        if is_rollout(&path) || path.exists() {
+            expanded.insert(path);
+            continue;
+        }
+        match catalog_paths_under(&path) {
+            Ok(under) => expanded.extend(under),
+            Err(e) => eprintln!("Failed to read session catalog: {}", e),
+        }
+    }
+    expanded.into_iter().collect()
+}
+
+/// Changed paths waiting for writes to them to settle, with when each was
+/// first and last reported.
+#[derive(Default)]
+struct PendingPaths {
+    paths: HashMap<PathBuf, (Instant, Instant)>,
+}
+
+impl PendingPaths {
+    fn add(&mut self, paths: Vec<PathBuf>, now: Instant) {
+        for path in paths {
+            self.paths.entry(path).or_insert((now, now)).1 = now;
+        }
+    }
+
+    fn due_at(first: Instant, last: Instant) -> Instant {
+        (last + QUIET_PERIOD).min(first + MAX_DELAY)
+    }
+
+    /// Removes and returns the paths that are due at `now`.
+    fn take_due(&mut self, now: Instant) -> Vec<PathBuf> {
+        let due: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .filter(|(_, &(first, last))| Self::due_at(first, last) <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &due {
+            self.paths.remove(path);
+        }
+        due
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.paths
+            .values()
+            .map(|&(first, last)| Self::due_at(first, last))
+            .min()
+    }
+}
+
+/// Loads the catalog, then applies changed rollout paths once writes to them
+/// settle. The catalog on disk is only written when changes pause.
+fn process_changes(receiver: mpsc::Receiver<Vec<PathBuf>>) {
+    // Whatever changed before the watcher started is picked up here.
+    if let Err(e) = refresh_catalog() {
+        eprintln!("Failed to load session catalog: {}", e);
+    }
+    let mut pending = PendingPaths::default();
+    let mut last_change = Instant::now();
+    let mut unsaved_since: Option<Instant> = None;
+    loop {
+        let save_due =
+            unsaved_since.map(|since| (last_change + SAVE_DELAY).min(since + SAVE_INTERVAL));
+        let received = match pending.next_due().into_iter().chain(save_due).min() {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => receiver
+                .recv()
+                .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(paths) => pending.add(paths, Instant::now()),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        let now = Instant::now();
+        let due = pending.take_due(now);
+        if !due.is_empty() {
+            match update_catalog_paths(&expand_removed(due)) {
+                Ok(updates) if !updates.is_empty() => {
+                    last_change = now;
+                    unsaved_since.get_or_insert(now);
+                    publish(updates);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to update session catalog: {}", e),
+            }
+        }
+        if unsaved_since
+            .is_some_and(|since| now >= last_change + SAVE_DELAY || now >= since + SAVE_INTERVAL)
+        {
+            if let Err(e) = save_catalog() {
+                eprintln!("Failed to save session catalog: {}", e);
+            }
+            unsaved_since = None;
+        }
+    }
+    if let Err(e) = save_catalog() {
+        eprintln!("Failed to save session catalog: {}", e);
+    }
+}
+
+/// Whether the session watcher is running, i.e. keeps the in-memory catalog
+/// current.
+pub fn is_watching() -> bool {
+    WATCHER.lock().is_ok_and(|watcher| watcher.is_some())
+}
+
+/// Watches the sessions directory and pushes `session-added`,
+/// `session-updated` and `session-removed` events as rollouts change.
+/// Calling it again while a watcher is running does nothing.
+pub fn start_session_watcher(app: AppHandle) -> Result<(), String> {
+    // Changes the app makes itself are published even if watching fails.
+    let _ = APP.set(app);
+    let mut watcher_slot = WATCHER
+        .lock()
+        .map_err(|e| format!("Session watcher lock poisoned: {}", e))?;
+    if watcher_slot.is_some() {
+        return Ok(());
+    }
+
+    let sessions_dir = get_sessions_path()?;
+    std::fs::create_dir_all(&sessions_dir)
+        .map_err(|e| format!("Failed to create sessions dir: {}", e))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let event = match result {
+            Ok(event) if !event.need_rescan() => event,
+            // Events were dropped, so the catalog may have missed changes.
+            result => {
+                if let Err(e) = result {
+                    eprintln!("Session watcher error: {}", e);
+                }
+                if let Err(e) = invalidate_catalog() {
+                    eprintln!("{}", e);
+                }
+                return;
+            }
+        };
+        let paths = changed_rollouts(event);
+        if !paths.is_empty() {
+            let _ = sender.send(paths);
+        }
+    })
+    .map_err(|e| format!("Failed to create session watcher: {}", e))?;
+    watcher
+        .watch(&sessions_dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {:?}: {}", sessions_dir, e))?;
+
+    std::thread::Builder::new()
+        .name("session-watcher".to_string())
+        .spawn(move || process_changes(receiver))
+        .map_err(|e| format!("Failed to start session watcher: {}", e))?;
+    *watcher_slot = Some(watcher);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_wait_until_quiet() {
+        let start = Instant::now();
+        let mut pending = PendingPaths::default();
+        pending.add(vec![PathBuf::from("a.jsonl")], start);
+        assert!(pending.take_due(start + QUIET_PERIOD / 2).is_empty());
+
+        // Another write pushes it back.
+        pending.add(vec![PathBuf::from("a.jsonl")], start + QUIET_PERIOD / 2);
+        assert!(pending.take_due(start + QUIET_PERIOD).is_empty());
+        assert_eq!(pending.next_due(), Some(start + QUIET_PERIOD * 3 / 2));
+        assert_eq!(
+            pending.take_due(start + QUIET_PERIOD * 3 / 2),
+            vec![PathBuf::from("a.jsonl")]
+        );
+        assert_eq!(pending.next_due(), None);
+    }
+
+    #[test]
+    fn busy_paths_are_applied_after_max_delay() {
+        let start = Instant::now();
+        let mut pending = PendingPaths::default();
+        let step = QUIET_PERIOD / 2;
+        let mut now = start;
+        while now < start + MAX_DELAY {
+            pending.add(vec![PathBuf::from("a.jsonl")], now);
+            assert!(pending.take_due(now).is_empty());
+            now += step;
+        }
+        assert_eq!(pending.take_due(now), vec![PathBuf::from("a.jsonl")]);
+    }
+}
@@ -1,6 +1,12 @@
-import { For, Show, createMemo, createSignal, onMount } from "solid-js";
+import { For, Show, createMemo, createSignal, onCleanup, onMount } from "solid-js";
 import { invoke } from "@tauri-apps/api/core";
-import type { ConversationSummary } from "@/types/session";
+import { listen } from "@tauri-apps/api/event";
+import type {
+  ConversationSummary,
+  ScanProgress,
+  ScanResult,
+  SessionChangeEvent,
+} from "@/types/session";
 import { Link } from "@/ui";
 import { TbReload } from "solid-icons/tb";
 
@@ -38,23 +44,34 @@ const ConversationsList = (props: ConversationsListProps) => {
     selectedSessionIds,
   });
 
+  const scanId = `sessions:${props.projectPath}`;
+
   const fetchSessions = async () => {
     setIsLoading(true);
     setError(null);
 
+    // Show sessions as the scan finds them; the final list below replaces
+    // them with entries that carry titles, tags and notes.
+    const unlisten = await listen<ScanProgress>("session-scan-progress", (event) => {
+      if (event.payload.scanId !== scanId || event.payload.sessions.length === 0) return;
+      setSessions((prev) => {
+        const known = new Set(prev.map((s) => s.conversationId));
+        const added = event.payload.sessions.filter((s) => !known.has(s.conversationId));
+        return [...prev, ...added];
+      });
+    });
+
     try {
       console.log(props.projectPath);
-      const response = await invoke<{ sessions: ConversationSummary[] }>(
-        "get_project_sessions",
-        { projectPath: props.projectPath },
-      );
-      console.log(response);
-      const sessionList: ConversationSummary[] = response.sessions;
-
-      setSessions(sessionList);
+      const result = await invoke<ScanResult>("start_session_scan", {
+        scanId,
+        projectPath: props.projectPath,
+      });
+      if (!result.cancelled) setSessions(result.sessions);
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
     } finally {
+      unlisten();
       setIsLoading(false);
     }
   };
@@ -63,11 +80,45 @@ const ConversationsList = (props: ConversationsListProps) => {
     void fetchSessions();
   });
 
+  // Live updates from the session watcher while `codex` runs elsewhere.
+  const upsertSession = ({ projectPath, session }: SessionChangeEvent) => {
+    if (projectPath !== props.projectPath || !session) return;
+    setSessions((prev) => {
+      const index = prev.findIndex((s) => s.path === session.path);
+      if (index === -1) return [session, ...prev];
+      // Keep the title, tags and notes the list already has.
+      const next = [...prev];
+      next[index] = { ...prev[index], ...session };
+      return next;
+    });
+  };
+
+  const unlistenChanges = Promise.all([
+    listen<SessionChangeEvent>("session-added", (event) => upsertSession(event.payload)),
+    listen<SessionChangeEvent>("session-updated", (event) => upsertSession(event.payload)),
+    listen<SessionChangeEvent>("session-renamed", (event) => {
+      const { previousPath, session } = event.payload;
+      setSessions((prev) =>
+        prev.map((s) => (s.path === previousPath && session ? { ...s, ...session } : s)),
+      );
+      upsertSession(event.payload);
+    }),
+    listen<SessionChangeEvent>("session-removed", (event) => {
+      setSessions((prev) => prev.filter((s) => s.path !== event.payload.path));
+    }),
+  ]);
+
+  onCleanup(() => {
+    void invoke("cancel_session_scan", { scanId });
+    void unlistenChanges.then((unlisteners) => unlisteners.forEach((unlisten) => unlisten()));
+  });
+
   const visibleSessions = createMemo(() => {
     const query = searchQuery().toLowerCase().trim();
     if (!query) return sessions();
     return sessions().filter(
       (s) =>
+        (s.title ?? "").toLowerCase().includes(query) ||
         s.preview.toLowerCase().includes(query) ||
         s.conversationId.toLowerCase().includes(query),
     );
@@ -225,16 +276,14 @@ const ConversationsList = (props: ConversationsListProps) => {
                           onClick={() => handleSelect(session)}
                         >
                           <span class="text-sm font-medium text-slate-100 group-hover:text-slate-50">
-                            {session.preview && session.preview.length > 0
-                              ? session.preview
-                              : session.conversationId}
+                            {session.title || session.preview || session.conversationId}
                           </span>
                         </button>
                       }
                     >
                       <input
                         type="text"
-                        value={session.preview || session.conversationId}
+                        value={session.title || session.preview || session.conversationId}
                         onBlur={(e) =>
                           handleRename(session, e.currentTarget.value)
                         }
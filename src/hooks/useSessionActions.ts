@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { createSignal } from "solid-js";
-import type { ConversationSummary } from "@/types/session";
+import type { ConversationSummary, TrashOutcome } from "@/types/session";
 
 interface UseSessionActionsProps {
   projectPath: string;
@@ -45,19 +45,34 @@ export const useSessionActions = (props: UseSessionActionsProps) => {
 
   const handleBatchDelete = async (sessionIdsToDelete: string[]) => {
     try {
-      await invoke("delete_sessions_files", {
+      const outcomes = await invoke<TrashOutcome[]>("delete_sessions_files", {
         projectPath: props.projectPath,
         sessionPaths: props.sessions()
           .filter((s) => sessionIdsToDelete.includes(s.conversationId))
           .map((s) => s.path),
       });
+      const deletedPaths = new Set(
+        outcomes.filter((o) => o.entry).map((o) => o.path),
+      );
+      const deletedIds = props.sessions()
+        .filter((s) => deletedPaths.has(s.path))
+        .map((s) => s.conversationId);
       props.setSessions((prevSessions) =>
-        prevSessions.filter((s) => !sessionIdsToDelete.includes(s.conversationId)),
+        prevSessions.filter((s) => !deletedPaths.has(s.path)),
       );
-      if (sessionIdsToDelete.includes(props.selectedKey() || "")) {
+      if (deletedIds.includes(props.selectedKey() || "")) {
         props.onSelect(null);
       }
-      props.setSelectedSessionIds(new Set()); // Clear all selections
+      // Keep failed sessions selected so they can be retried
+      props.setSelectedSessionIds((prev) => {
+        const newSet = new Set(prev);
+        deletedIds.forEach((id) => newSet.delete(id));
+        return newSet;
+      });
+      const failures = outcomes.filter((o) => o.error);
+      if (failures.length > 0) {
+        setError(failures.map((o) => o.error).join("\n"));
+      }
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
     }
@@ -69,15 +84,14 @@ export const useSessionActions = (props: UseSessionActionsProps) => {
       return;
     }
     try {
-      await invoke("update_cache_title", {
-        projectPath: props.projectPath,
-        sessionPath: session.path,
-        preview: newTitle.trim(),
+      await invoke("update_session_title", {
+        conversationId: session.conversationId,
+        title: newTitle.trim(),
       });
       props.setSessions((prevSessions) =>
         prevSessions.map((s) =>
           s.conversationId === session.conversationId
-            ? { ...s, preview: newTitle.trim() }
+            ? { ...s, title: newTitle.trim() }
             : s,
         ),
       );
@@ -2,6 +2,20 @@ export interface ConversationSummary {
   conversationId: string;
   path: string;
   preview: string;
+  title?: string;
+  tags?: string[];
+  starred?: boolean;
+  notes?: string;
+  startedAt?: string;
+  lastEventAt?: string;
+  durationSeconds?: number;
+  userTurns?: number;
+  toolCalls?: number;
+  patchesApplied?: number;
+  totalTokens?: number;
+  model?: string;
+  cliVersion?: string;
+  gitBranch?: string;
 }
 
 export interface Plan {
@@ -21,3 +35,76 @@ export interface SessionMessage {
   variant?: MessageVariant;
   title?: string;
 }
+
+export type SessionEventKind =
+  | "user"
+  | "assistant"
+  | "tool_call"
+  | "tool_result"
+  | "error"
+  | "meta";
+
+// Mirrors docs/session_event.schema.json, returned by `get_session_events`.
+export interface SessionEvent {
+  id: string;
+  kind: SessionEventKind;
+  type: string;
+  rawJSON: string;
+  timestamp?: string;
+  role?: string;
+  text?: string;
+  toolName?: string;
+  toolInput?: string;
+  toolOutput?: string;
+  messageID?: string;
+  parentID?: string;
+  isDelta?: boolean;
+  model?: string;
+  encrypted_content?: string;
+}
+
+// Returned by `list_trash` and inside `TrashOutcome`.
+export interface TrashEntry {
+  id: string;
+  batchId: string;
+  originalPath: string;
+  projectPath: string;
+  sizeBytes: number;
+  deletedAt: string;
+}
+
+// One file of `delete_sessions_files`, `restore_sessions` or `undo_delete`.
+export interface TrashOutcome {
+  path: string;
+  entry?: TrashEntry;
+  error?: string;
+}
+
+// Returned by `start_session_scan`; `sessions` is empty when it was cancelled.
+export interface ScanResult {
+  cancelled: boolean;
+  sessions: ConversationSummary[];
+}
+
+// Payload of the `session-scan-progress` event emitted by `start_session_scan`.
+export interface ScanProgress {
+  scanId: string;
+  done: number;
+  total: number;
+  sessions: (ConversationSummary & { projectPath?: string })[];
+}
+
+// Payload of the `session-added`, `session-updated`, `session-renamed` and
+// `session-removed` events.
+export interface SessionChangeEvent {
+  path: string;
+  previousPath?: string;
+  projectPath?: string;
+  session?: ConversationSummary;
+}
+
+// Sent over the channel passed to `follow_session`.
+export type FollowMessage =
+  | { type: "event"; event: SessionEvent }
+  | { type: "reset" }
+  | { type: "stopped" };